@@ -1,4 +1,3 @@
-#![feature(step_trait)]
 #![feature(binary_heap_into_iter_sorted)]
 #![feature(pin_macro)]
 #![feature(result_option_inspect)]
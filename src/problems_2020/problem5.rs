@@ -1,5 +1,6 @@
 use itertools::Itertools;
 
+use crate::util::bool_helper::vec_to_number;
 use crate::util::parse::read_lines;
 
 pub static INPUT_PATH: &str = "src/problems_2020/problem5/input.txt";
@@ -62,17 +63,11 @@ pub fn solve_part2(input: &[(Vec<bool>, Vec<bool>)]) -> i32 {
     panic!();
 }
 
-fn binary_space_partition(flags: &[bool], mut min: i32, mut max: i32) -> i32 {
-    for flag in flags {
-        let middle = min + (max - min) / 2;
-        if *flag {
-            min = middle + 1;
-        } else {
-            max = middle;
-        }
-    }
-    assert_eq!(min, max);
-    min
+// Each flag keeps the lower (false) or upper (true) half of min..=max, so the
+// flags are the offset from min written in binary.
+fn binary_space_partition(flags: &[bool], min: i32, max: i32) -> i32 {
+    assert_eq!(max - min + 1, 1 << flags.len());
+    min + vec_to_number(flags) as i32
 }
 
 #[cfg(test)]
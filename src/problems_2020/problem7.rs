@@ -1,5 +1,6 @@
+use crate::util::graph::{evaluate, reverse_reachable};
 use crate::util::parse::read_lines;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 pub static INPUT_PATH: &str = "src/problems_2020/problem7/input.txt";
 
 pub fn parse_line(line: &str) -> (String, Vec<(i32, String)>) {
@@ -29,47 +30,34 @@ pub fn parse_input(path_to_input: &str) -> HashMap<String, Vec<(i32, String)>> {
         .collect()
 }
 
-pub fn find_bag(
-    input: &HashMap<String, Vec<(i32, String)>>,
-    key: &str,
-    target: &str,
-) -> Option<String> {
-    if key == target {
-        return None;
-    }
-    let mut stack = HashSet::new();
-    stack.insert(key);
-    while !stack.is_empty() {
-        let current = *stack.iter().next().unwrap();
-        stack.remove(current);
-        if current == target {
-            return Some(key.to_string());
-        }
-        for (_, value) in input.get(current).unwrap() {
-            stack.insert(value);
-        }
-    }
-    None
+fn contents(input: &HashMap<String, Vec<(i32, String)>>, bag: &String) -> Vec<String> {
+    input[bag].iter().map(|(_, color)| color.clone()).collect()
 }
 
 pub fn solve_part1(input: &HashMap<String, Vec<(i32, String)>>) -> usize {
-    input
-        .iter()
-        .filter_map(|(key, _)| find_bag(input, key, "shiny gold"))
-        .count()
-}
-
-pub fn sum_bags(input: &HashMap<String, Vec<(i32, String)>>, key: &str) -> i32 {
-    let v = input.get(key).unwrap();
-    let mut sum = 0;
-    for (c, k) in v {
-        sum += c * sum_bags(input, k);
-    }
-    sum + 1
+    reverse_reachable(
+        input.keys().cloned(),
+        |bag| contents(input, bag),
+        &"shiny gold".to_string(),
+    )
+    .len()
 }
 
 pub fn solve_part2(input: &HashMap<String, Vec<(i32, String)>>) -> usize {
-    (sum_bags(input, "shiny gold") - 1) as usize
+    // Every bag counts itself plus all the bags inside of it
+    let bags = evaluate(
+        ["shiny gold".to_string()],
+        |bag| contents(input, bag),
+        |bag, inner: Vec<&i32>| {
+            1 + input[bag]
+                .iter()
+                .zip(inner)
+                .map(|((count, _), bags)| count * bags)
+                .sum::<i32>()
+        },
+    )
+    .unwrap_or_else(|cycle| panic!("Bags can't contain themselves: {}", cycle));
+    (bags["shiny gold"] - 1) as usize
 }
 
 #[cfg(test)]
@@ -1,72 +1,67 @@
-use crate::util::parse::read_lines_with_separator;
+use crate::util::parse::read_lines;
+use crate::util::vm::{operand, parse_program, repair, split_instruction, Flow, Machine, Stop, Vm};
 
 pub static INPUT_PATH: &str = "src/problems_2020/problem8/input.txt";
 
-pub fn parse_input(path_to_input: &str) -> Vec<(String, i32)> {
-    read_lines_with_separator(path_to_input, ' ')
-        .into_iter()
-        .map(|line| {
-            assert_eq!(line.len(), 2);
-            (line[0].to_string(), line[1].parse::<i32>().unwrap())
-        })
-        .collect()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Nop(i32),
+    Acc(i32),
+    Jmp(i32),
 }
 
-fn exec_instruction(instruction: &str, arg: i32, i_pointer: &mut i32, accumulator: &mut i32) {
-    match instruction {
-        "nop" => *i_pointer += 1,
-        "acc" => {
-            *accumulator += arg;
-            *i_pointer += 1
-        }
-        "jmp" => *i_pointer += arg,
-        _ => panic!("Unknown instruction {}", instruction),
-    }
+#[derive(Debug, Default)]
+pub struct Handheld {
+    accumulator: i32,
 }
 
-pub fn solve_part1(input: &[(String, i32)]) -> i32 {
-    let mut accumulator = 0;
-    let mut ins_watch = vec![false; input.len()];
-    let mut i: i32 = 0;
-    while (i as usize) < input.len() {
-        let (instruction, arg) = &input[i as usize];
-        if ins_watch[i as usize] {
-            return accumulator;
-        } else {
-            ins_watch[i as usize] = true;
+impl Machine for Handheld {
+    type Instruction = Instruction;
+
+    fn parse_instruction(line: &str) -> Result<Instruction, String> {
+        let (opcode, operands) = split_instruction(line);
+        let arg = operand(&operands, 0)?;
+        match opcode {
+            "nop" => Ok(Instruction::Nop(arg)),
+            "acc" => Ok(Instruction::Acc(arg)),
+            "jmp" => Ok(Instruction::Jmp(arg)),
+            _ => Err(format!("Unknown instruction {}", opcode)),
         }
+    }
 
-        exec_instruction(instruction, *arg, &mut i, &mut accumulator);
+    fn execute(&mut self, instruction: &Instruction) -> Result<Flow, String> {
+        Ok(match *instruction {
+            Instruction::Nop(_) => Flow::Next,
+            Instruction::Acc(arg) => {
+                self.accumulator += arg;
+                Flow::Next
+            }
+            Instruction::Jmp(arg) => Flow::Jump(arg as i64),
+        })
     }
-    accumulator
 }
 
-pub fn solve_part2(input: &[(String, i32)]) -> i32 {
-    for j in 0..input.len() {
-        let mut input_cpy = input.to_vec();
-        if input_cpy[j].0 == "nop" {
-            input_cpy[j].0 = "jmp".to_string();
-        } else if input_cpy[j].0 == "jmp" {
-            input_cpy[j].0 = "nop".to_string();
-        }
-
-        let mut accumulator = 0;
-        let mut ins_watch = vec![false; input_cpy.len()];
-        let mut i: i32 = 0;
-        while (i as usize) < input_cpy.len() {
-            let (instruction, arg) = &input_cpy[i as usize];
-            if ins_watch[i as usize] {
-                break;
-            }
-            ins_watch[i as usize] = true;
+pub fn parse_input(path_to_input: &str) -> Vec<Instruction> {
+    parse_program::<Handheld>(&read_lines(path_to_input).join("\n"))
+        .unwrap_or_else(|error| panic!("{}", error))
+}
 
-            exec_instruction(instruction, *arg, &mut i, &mut accumulator);
-        }
-        if (i as usize) == input_cpy.len() {
-            return accumulator;
-        }
+pub fn solve_part1(input: &[Instruction]) -> i32 {
+    let mut vm = Vm::new(Handheld::default(), input).detect_loops();
+    match vm.run() {
+        Stop::Loop(_) | Stop::Halted => vm.machine().accumulator,
+        stop => panic!("Unexpected stop {:?}", stop),
     }
-    panic!("No solution found")
+}
+
+pub fn solve_part2(input: &[Instruction]) -> i32 {
+    let swap = |instruction: &Instruction| match *instruction {
+        Instruction::Nop(arg) => Some(Instruction::Jmp(arg)),
+        Instruction::Jmp(arg) => Some(Instruction::Nop(arg)),
+        Instruction::Acc(_) => None,
+    };
+    let (_, handheld) = repair(input, Handheld::default, swap).expect("No solution found");
+    handheld.accumulator
 }
 
 #[cfg(test)]
@@ -1,5 +1,6 @@
-use crate::util::multiple_sum_problem::two_sum;
+use crate::util::multiple_sum_problem::contiguous_sum;
 use crate::util::parse::parse_to;
+use crate::util::window::PairSums;
 use itertools::Itertools;
 
 pub static INPUT_PATH: &str = "src/problems_2020/problem9/input.txt";
@@ -9,11 +10,12 @@ pub fn parse_input(path_to_input: &str) -> Vec<i64> {
 }
 
 pub fn sliding_sum(input: &[i64], window_size: usize) -> i64 {
-    for window in input.windows(window_size + 1) {
-        let pairs = two_sum(&window[0..window_size], window.last().unwrap());
-        if pairs.is_empty() {
-            return *window.last().unwrap();
+    let mut window = PairSums::new(window_size);
+    for &number in input {
+        if window.is_full() && !window.has_pair_sum(number) {
+            return number;
         }
+        window.push(number);
     }
     panic!("No found");
 }
@@ -22,21 +24,9 @@ pub fn solve_part1(input: &[i64]) -> i64 {
 }
 
 pub fn solve_part2(input: &[i64]) -> i64 {
-    let sum = 85848519;
-    for i in 0..input.len() {
-        let mut current_sum = 0;
-        for j in i..input.len() {
-            current_sum += input[j];
-            if current_sum == sum && i != j {
-                let set = &input[i..=j];
-                let minmax = set.iter().minmax().into_option().unwrap();
-                return minmax.0 + minmax.1;
-            } else if current_sum > sum {
-                break;
-            }
-        }
-    }
-    panic!("Not found");
+    let range = contiguous_sum(input, solve_part1(input), 2).expect("Not found");
+    let minmax = input[range].iter().minmax().into_option().unwrap();
+    minmax.0 + minmax.1
 }
 
 #[cfg(test)]
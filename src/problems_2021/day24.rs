@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     panic,
 };
 
@@ -7,6 +7,7 @@ use itertools::Itertools;
 use log::{debug, info};
 
 use crate::util::shortest_path::{shortest_path, Edge};
+use crate::util::vm::{parse_program, split_instruction, Flow, Machine, Stop, Vm};
 
 macro_rules! INPUT_PATH {
     () => {
@@ -49,87 +50,27 @@ pub enum Instruction {
     EqlScalar((Register, Number)),
 }
 
-fn parse_register_or_number(input: &str) -> Source {
-    match input {
+fn parse_register_or_number(input: &str) -> Result<Source, String> {
+    Ok(match input {
         "w" => Source::Register(0),
         "x" => Source::Register(1),
         "y" => Source::Register(2),
         "z" => Source::Register(3),
-        _ => Source::Number(input.parse::<Number>().unwrap()),
-    }
+        _ => Source::Number(
+            input
+                .parse::<Number>()
+                .map_err(|_| format!("Expected register or number {}", input))?,
+        ),
+    })
 }
 
-fn parse_register(input: &str) -> Register {
-    match parse_register_or_number(input) {
-        Source::Register(register) => register,
-        Source::Number(_) => panic!("Expected register not number {}", input),
+fn parse_register(input: &str) -> Result<Register, String> {
+    match parse_register_or_number(input)? {
+        Source::Register(register) => Ok(register),
+        Source::Number(_) => Err(format!("Expected register not number {}", input)),
     }
 }
 
-pub fn parse(input: &str) -> Input {
-    let result = input
-        .lines()
-        .map(|line| {
-            let parts = line.split_whitespace().collect_vec();
-            match parts[0] {
-                "inp" => {
-                    assert_eq!(parts.len(), 2);
-                    Instruction::Inp(parse_register(parts[1]))
-                }
-                "add" => {
-                    assert_eq!(parts.len(), 3);
-                    let dest = parse_register(parts[1]);
-                    match parse_register_or_number(parts[2]) {
-                        Source::Register(source) => Instruction::Add((dest, source)),
-                        Source::Number(source) => Instruction::AddScalar((dest, source)),
-                    }
-                }
-                "mul" => {
-                    assert_eq!(parts.len(), 3);
-                    let dest = parse_register(parts[1]);
-                    match parse_register_or_number(parts[2]) {
-                        Source::Register(source) => Instruction::Mul((dest, source)),
-                        Source::Number(source) => Instruction::MulScalar((dest, source)),
-                    }
-                }
-                "div" => {
-                    assert_eq!(parts.len(), 3);
-                    let dest = parse_register(parts[1]);
-                    match parse_register_or_number(parts[2]) {
-                        Source::Register(source) => Instruction::Div((dest, source)),
-                        Source::Number(source) => Instruction::DivScalar((dest, source)),
-                    }
-                }
-                "mod" => {
-                    assert_eq!(parts.len(), 3);
-                    let dest = parse_register(parts[1]);
-                    match parse_register_or_number(parts[2]) {
-                        Source::Register(source) => Instruction::Mod((dest, source)),
-                        Source::Number(source) => Instruction::ModScalar((dest, source)),
-                    }
-                }
-                "eql" => {
-                    assert_eq!(parts.len(), 3);
-                    let dest = parse_register(parts[1]);
-                    match parse_register_or_number(parts[2]) {
-                        Source::Register(source) => Instruction::Eql((dest, source)),
-                        Source::Number(source) => Instruction::EqlScalar((dest, source)),
-                    }
-                }
-                _ => panic!("Unexpected line {:?}", parts),
-            }
-        })
-        .collect_vec()
-        .chunks_exact(18)
-        .map(|chunk| {
-            assert_eq!(chunk[0], Instruction::Inp(0));
-            chunk.to_vec()
-        })
-        .collect_vec();
-    debug!("{:?}", result);
-    result
-}
-
 fn divide(a: Number, b: Number) -> Result<Number, String> {
     if b == 0 {
         Err("Cannot divide by 0".to_owned())
@@ -150,15 +91,50 @@ fn eq(a: Number, b: Number) -> Number {
     (a == b) as Number
 }
 
-fn compute_next_state(
-    instructions: &[Instruction],
-    old_z_state: Number,
-    next_input: Number,
-) -> Result<Number, String> {
-    let mut registers = [0, 0, 0, old_z_state];
-    for instruction in instructions {
+// The submarine's arithmetic logic unit, registers w, x, y and z. Every `inp` takes the
+// next value from the front of `input`.
+struct Alu {
+    registers: [Number; 4],
+    input: VecDeque<Number>,
+}
+
+impl Machine for Alu {
+    type Instruction = Instruction;
+
+    fn parse_instruction(line: &str) -> Result<Instruction, String> {
+        let (opcode, parts) = split_instruction(line);
+        if opcode == "inp" {
+            if parts.len() != 1 {
+                return Err(format!("Expected one operand {:?}", parts));
+            }
+            return Ok(Instruction::Inp(parse_register(parts[0])?));
+        }
+        if parts.len() != 2 {
+            return Err(format!("Expected two operands {:?}", parts));
+        }
+        let dest = parse_register(parts[0])?;
+        let source = parse_register_or_number(parts[1])?;
+        Ok(match (opcode, source) {
+            ("add", Source::Register(source)) => Instruction::Add((dest, source)),
+            ("add", Source::Number(source)) => Instruction::AddScalar((dest, source)),
+            ("mul", Source::Register(source)) => Instruction::Mul((dest, source)),
+            ("mul", Source::Number(source)) => Instruction::MulScalar((dest, source)),
+            ("div", Source::Register(source)) => Instruction::Div((dest, source)),
+            ("div", Source::Number(source)) => Instruction::DivScalar((dest, source)),
+            ("mod", Source::Register(source)) => Instruction::Mod((dest, source)),
+            ("mod", Source::Number(source)) => Instruction::ModScalar((dest, source)),
+            ("eql", Source::Register(source)) => Instruction::Eql((dest, source)),
+            ("eql", Source::Number(source)) => Instruction::EqlScalar((dest, source)),
+            _ => return Err(format!("Unexpected line {}", line)),
+        })
+    }
+
+    fn execute(&mut self, instruction: &Instruction) -> Result<Flow, String> {
+        let registers = &mut self.registers;
         match instruction {
-            Instruction::Inp(dest) => registers[*dest] = next_input,
+            Instruction::Inp(dest) => {
+                registers[*dest] = self.input.pop_front().ok_or("Input is empty")?;
+            }
             Instruction::Add((dest, src)) => registers[*dest] += registers[*src],
             Instruction::AddScalar((dest, scalar)) => registers[*dest] += scalar,
             Instruction::Mul((dest, src)) => registers[*dest] *= registers[*src],
@@ -182,9 +158,38 @@ fn compute_next_state(
                 registers[*dest] = eq(registers[*dest], *scalar);
             }
         }
+        Ok(Flow::Next)
     }
+}
 
-    Ok(registers[3])
+pub fn parse(input: &str) -> Input {
+    let result = parse_program::<Alu>(input)
+        .unwrap_or_else(|error| panic!("{}", error))
+        .chunks_exact(18)
+        .map(|chunk| {
+            assert_eq!(chunk[0], Instruction::Inp(0));
+            chunk.to_vec()
+        })
+        .collect_vec();
+    debug!("{:?}", result);
+    result
+}
+
+fn compute_next_state(
+    instructions: &[Instruction],
+    old_z_state: Number,
+    next_input: Number,
+) -> Result<Number, String> {
+    let alu = Alu {
+        registers: [0, 0, 0, old_z_state],
+        input: VecDeque::from([next_input]),
+    };
+    let mut vm = Vm::new(alu, instructions);
+    match vm.run() {
+        Stop::Halted => Ok(vm.machine().registers[3]),
+        Stop::Fault { message, .. } => Err(message),
+        stop => panic!("Unexpected stop {:?}", stop),
+    }
 }
 
 type ZState = Number;
@@ -237,10 +242,36 @@ pub fn solve_part2(input: &Input) -> (Number, Number) {
 
 #[cfg(test)]
 mod tests {
-
+    use super::*;
     use test_log::test;
+
     #[test]
     fn part12() {
         //assert_eq!(solve_part1(&parse(include_str!(INPUT_PATH!()))), (0, 0));
     }
+
+    #[test]
+    fn inputs_in_order() {
+        let program = parse_program::<Alu>("inp w\ninp x\nmul w 10\nadd w x").unwrap();
+        let alu = Alu {
+            registers: [0; 4],
+            input: VecDeque::from([1, 2]),
+        };
+        let mut vm = Vm::new(alu, &program);
+        assert_eq!(vm.run(), Stop::Halted);
+        assert_eq!(vm.machine().registers[0], 12);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Alu::parse_instruction("inp 3"),
+            Err("Expected register not number 3".to_string())
+        );
+        assert_eq!(
+            Alu::parse_instruction("add w v"),
+            Err("Expected register or number v".to_string())
+        );
+        assert!(parse_program::<Alu>("inp w\nmul q 2").is_err());
+    }
 }
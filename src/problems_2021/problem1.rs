@@ -1,4 +1,5 @@
 use crate::util::parse::parse_to;
+use crate::util::window::RunningSum;
 
 pub static INPUT_PATH: &str = "src/problems_2021/problem1/input.txt";
 
@@ -13,13 +14,18 @@ pub fn solve_part1(input: &[i32]) -> i32 {
 }
 
 pub fn solve_part2(input: &[i32]) -> i32 {
-    input
-        .windows(3)
-        .map(|window| window.iter().sum())
-        .collect::<Vec<i32>>()
-        .windows(2)
-        .map(|pair| i32::from(pair[0] < pair[1]))
-        .sum()
+    let mut window = RunningSum::new(3);
+    let mut previous = None;
+    let mut increases = 0;
+    for &depth in input {
+        window.push(depth);
+        if window.is_full() {
+            let sum = window.sum();
+            increases += i32::from(previous.is_some_and(|previous| previous < sum));
+            previous = Some(sum);
+        }
+    }
+    increases
 }
 
 #[cfg(test)]
@@ -1,9 +1,12 @@
 #![allow(clippy::ptr_arg)]
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use itertools::Itertools;
 
-use crate::util::parse::read_lines;
+use crate::util::{
+    graph::{count_paths, VisitPolicy},
+    parse::read_lines,
+};
 
 pub static INPUT_PATH: &str = "src/problems_2021/problem12/input.txt";
 
@@ -41,41 +44,51 @@ fn is_small(edge: &str) -> bool {
     edge.chars().all(char::is_lowercase)
 }
 
-fn bfs_rec(
-    adjacency_list: &Input,
-    current: &str,
-    end: &str,
-    mut visited: HashMap<Edge, i32>,
-    visit: &dyn Fn(&Edge, &HashMap<Edge, i32>) -> bool,
-) -> i32 {
-    let mut paths = 0;
-    if current == end {
-        return 1;
-    }
-    if is_small(current) {
-        *visited.entry(current.to_string()).or_insert(0) += 1;
+// Small caves at most once, except for a single small cave that may be visited twice
+// if `revisit` is set. `start` is never entered again.
+struct Caves {
+    revisit: bool,
+}
+
+impl VisitPolicy<Edge> for Caves {
+    // Small caves visited so far and whether the single revisit has been used up
+    type State = (BTreeSet<Edge>, bool);
+
+    fn start(&self, start: &Edge) -> Self::State {
+        (BTreeSet::from([start.clone()]), !self.revisit)
     }
-    for neighbor in adjacency_list.get(current).unwrap() {
-        if visit(neighbor, &visited) {
-            paths += bfs_rec(adjacency_list, neighbor, end, visited.clone(), visit);
+
+    fn visit(&self, state: &Self::State, cave: &Edge) -> Option<Self::State> {
+        let (visited, revisited) = state;
+        if !is_small(cave) {
+            Some(state.clone())
+        } else if !visited.contains(cave) {
+            let mut visited = visited.clone();
+            visited.insert(cave.clone());
+            Some((visited, *revisited))
+        } else if !revisited && cave != "start" {
+            Some((visited.clone(), true))
+        } else {
+            None
         }
     }
-    paths
+}
+
+fn count_cave_paths(input: &Input, revisit: bool) -> i32 {
+    count_paths(
+        |cave| input[cave].iter().cloned().collect_vec(),
+        &"start".to_string(),
+        &"end".to_string(),
+        &Caves { revisit },
+    ) as i32
 }
 
 pub fn solve_part1(input: &Input) -> i32 {
-    bfs_rec(input, "start", "end", HashMap::new(), &|edge, visited| {
-        !visited.contains_key(edge)
-    })
+    count_cave_paths(input, false)
 }
 
 pub fn solve_part2(input: &Input) -> i32 {
-    bfs_rec(input, "start", "end", HashMap::new(), &|edge, visited| {
-        !visited.contains_key(edge)
-            || (visited.values().max().map_or(true, |max| *max == 1)
-                && edge != "start"
-                && edge != "end")
-    })
+    count_cave_paths(input, true)
 }
 
 #[cfg(test)]
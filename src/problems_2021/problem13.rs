@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use itertools::Itertools;
 
-use crate::util::parse::read_lines;
+use crate::util::{ocr::recognize_points, parse::read_lines};
 
 pub static INPUT_PATH: &str = "src/problems_2021/problem13/input.txt";
 
@@ -73,24 +73,11 @@ pub fn solve_part1(input: &Input) -> i32 {
     fold(dots.clone(), &instructions[0..1]).len() as i32
 }
 
-pub fn solve_part2(input: &Input) -> i32 {
+pub fn solve_part2(input: &Input) -> String {
     let (dots, instructions) = input;
     let pixels = fold(dots.clone(), instructions);
-    let mut image = vec![vec!["."]; 0];
-    for dot in pixels {
-        let x = dot.0 as usize;
-        let y = dot.1 as usize;
-        if y >= image.len() {
-            image.resize(y + 1, vec!["."]);
-        }
-        let row = &mut image[y];
-        if x >= row.len() {
-            row.resize(x + 1, ".")
-        }
-        image[y][x] = "#"
-    }
-    print!("{}", image.iter().map(|row| row.join(" ")).join("\n"));
-    0
+    recognize_points(pixels.into_iter().map(|(x, y)| (x as i64, y as i64)))
+        .expect("Folded dots should spell capital letters")
 }
 
 #[cfg(test)]
@@ -118,6 +105,6 @@ mod tests {
 
     #[test]
     fn part2() {
-        assert_eq!(solve_part2(&parse_input(INPUT_PATH)), 0);
+        assert_eq!(solve_part2(&parse_input(INPUT_PATH)), "PCPHARKL");
     }
 }
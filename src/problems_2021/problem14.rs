@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use itertools::Itertools;
 
-use crate::util::parse::read_lines;
+use crate::util::{matrix::CountTransitions, parse::read_lines};
 
 pub static INPUT_PATH: &str = "src/problems_2021/problem14/input.txt";
 
@@ -38,62 +38,50 @@ pub fn parse_input(path_to_input: &str) -> Input {
     (template, instructions)
 }
 
-fn iterate_polymer(input: &Input, steps: usize) -> Vec<char> {
-    let (mut polymer, instructions) = input.clone();
-    for _ in 0..steps {
-        let mut next = polymer.clone();
-        let mut index = 1;
-        for window in polymer.windows(2) {
-            let pair = Pair(window[0], window[1]);
-            if let Some(mapped) = instructions.get(&pair) {
-                next.insert(index, *mapped);
-                index += 1;
-            }
-            index += 1;
-        }
-        polymer = next;
-    }
-    polymer
+// Counts of adjacent pairs after `steps` insertion steps. Only pair counts are
+// tracked, the polymer itself grows exponentially.
+fn iterate_polymer(input: &Input, steps: u64) -> HashMap<Pair, u64> {
+    let (template, instructions) = input;
+    let template_pairs = template
+        .windows(2)
+        .map(|window| Pair(window[0], window[1]))
+        .collect_vec();
+    let states = template_pairs.iter().cloned().chain(
+        instructions
+            .iter()
+            .flat_map(|(&pair, &mapped)| [pair, Pair(pair.0, mapped), Pair(mapped, pair.1)]),
+    );
+    let pairs = CountTransitions::new(states, |&pair| match instructions.get(&pair) {
+        Some(&mapped) => vec![(Pair(pair.0, mapped), 1), (Pair(mapped, pair.1), 1)],
+        None => vec![(pair, 1)],
+    });
+    let counts = template_pairs
+        .into_iter()
+        .counts()
+        .into_iter()
+        .map(|(pair, count)| (pair, count as u64))
+        .collect();
+    pairs.after(&counts, steps)
 }
 
-pub fn solve_part1(input: &Input) -> i32 {
-    let polymer = iterate_polymer(input, 10);
+// Most common minus least common element after `steps` steps
+fn element_spread(input: &Input, steps: u64) -> u64 {
     let mut counts = HashMap::new();
-    // TODO find a more idomatic way
-    for c in polymer {
-        *counts.entry(c).or_insert(0) += 1;
+    // Every element is the first of a pair except for the last one, which never changes
+    for (pair, count) in iterate_polymer(input, steps) {
+        *counts.entry(pair.0).or_insert(0) += count;
     }
-    let values = counts.values().cloned().collect_vec();
-    values.iter().max().unwrap() - values.iter().min().unwrap()
+    *counts.entry(*input.0.last().unwrap()).or_insert(0) += 1;
+    let (min, max) = counts.values().minmax().into_option().unwrap();
+    max - min
 }
 
-pub fn solve_part2(input: &Input) -> usize {
-    let (template, instructions) = input;
-    let mut counts = HashMap::new();
-    for c in template {
-        *counts.entry(c).or_insert(0) += 1;
-    }
-    let mut pairs = HashMap::new();
+pub fn solve_part1(input: &Input) -> u64 {
+    element_spread(input, 10)
+}
 
-    for pair in template.windows(2) {
-        *pairs.entry(Pair(pair[0], pair[1])).or_insert(0) += 1
-    }
-    for _ in 0..40 {
-        let mut next = pairs.clone();
-        for (key, count) in pairs {
-            if let Some(mapped) = instructions.get(&key) {
-                let first = Pair(key.0, *mapped);
-                let second = Pair(*mapped, key.1);
-                *next.get_mut(&key).unwrap() -= count;
-                *next.entry(first).or_insert(0) += count;
-                *next.entry(second).or_insert(0) += count;
-                *counts.entry(mapped).or_insert(0) += count;
-            }
-        }
-        pairs = next;
-    }
-    let values = counts.values().cloned().collect_vec();
-    values.iter().max().unwrap() - values.iter().min().unwrap()
+pub fn solve_part2(input: &Input) -> u64 {
+    element_spread(input, 40)
 }
 
 #[cfg(test)]
@@ -3,7 +3,7 @@ use itertools::Itertools;
 use crate::util::{
     index::{expand, flatten},
     parse::read_lines,
-    shortest_path::{shortest_path, Edge},
+    shortest_path::{bucket_shortest_path, Edge},
 };
 
 pub static INPUT_PATH: &str = "src/problems_2021/problem15/input.txt";
@@ -32,14 +32,14 @@ pub fn parse_input(path_to_input: &str) -> Input {
 fn adj_list(grid: &[i32], dimensions: &(usize, usize)) -> Vec<Vec<Edge<usize>>> {
     grid.iter()
         .enumerate()
-        .map(|(index, risk)| {
+        .map(|(index, _)| {
             let (x, y) = expand(index as i32, dimensions);
             [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
                 .iter()
                 .filter_map(|pos| {
                     flatten(*pos, dimensions).map(|flat_index| Edge {
                         node: flat_index,
-                        cost: *risk as i64,
+                        cost: grid[flat_index] as i64,
                     })
                 })
                 .collect_vec()
@@ -49,12 +49,9 @@ fn adj_list(grid: &[i32], dimensions: &(usize, usize)) -> Vec<Vec<Edge<usize>>>
 
 pub fn solve_part1(input: &Input) -> i32 {
     let (dimensions, risk_map) = input;
-    let adj = adj_list(risk_map, dimensions)
-        .into_iter()
-        .enumerate()
-        .collect();
+    let adj = adj_list(risk_map, dimensions);
 
-    shortest_path(&adj, &0, &(adj.len() - 1)).unwrap() as i32
+    bucket_shortest_path(&adj, 0, adj.len() - 1, 9).unwrap() as i32
 }
 
 pub fn solve_part2(input: &Input) -> i32 {
@@ -74,12 +71,9 @@ pub fn solve_part2(input: &Input) -> i32 {
         })
         .collect_vec();
 
-    let adj = adj_list(&large_map, &large_dimensions)
-        .into_iter()
-        .enumerate()
-        .collect();
+    let adj = adj_list(&large_map, &large_dimensions);
 
-    shortest_path(&adj, &0, &(adj.len() - 1)).unwrap() as i32
+    bucket_shortest_path(&adj, 0, adj.len() - 1, 9).unwrap() as i32
 }
 
 #[cfg(test)]
@@ -102,12 +96,12 @@ mod tests {
 
     #[test]
     fn example2() {
-        //let input = parse_input(EXAMPLE_PATH);
-        //assert_eq!(solve_part2(&input), 315);
+        let input = parse_input(EXAMPLE_PATH);
+        assert_eq!(solve_part2(&input), 315);
     }
 
     #[test]
     fn part2() {
-        //assert_eq!(solve_part2(&parse_input(INPUT_PATH)), 2885);
+        assert_eq!(solve_part2(&parse_input(INPUT_PATH)), 2885);
     }
 }
@@ -1,8 +1,10 @@
-use std::{collections::VecDeque, panic};
+use std::panic;
 
 use itertools::Itertools;
 use log::info;
 
+use crate::util::bits::{BitError, BitReader};
+
 macro_rules! INPUT_PATH {
     () => {
         "problem16/input.txt"
@@ -19,22 +21,10 @@ pub fn solve() {
     );
 }
 
-type Input = VecDeque<bool>;
+type Input = BitReader;
 
 pub fn parse(input: &str) -> Input {
-    input
-        .trim()
-        .chars()
-        .filter_map(|b| match b {
-            w if w.is_whitespace() => None,
-            d if d.is_ascii_hexdigit() => {
-                let digit = d.to_digit(16).unwrap();
-                Some((0..4).rev().map(move |i| (digit & (1 << i)) != 0))
-            }
-            _ => panic!("Expected hex not {}", b),
-        })
-        .flatten()
-        .collect()
+    BitReader::from_hex(input.trim()).unwrap_or_else(|error| panic!("{}", error))
 }
 
 #[derive(Clone)]
@@ -95,48 +85,42 @@ impl Package {
     }
 }
 
-fn read_bits(bits: &mut dyn Iterator<Item = bool>, num_bits: usize) -> u64 {
-    bits.take(num_bits).fold(0, |a, b| a << 1 | b as u64)
-}
-
-pub fn parse_package(bits: &mut dyn Iterator<Item = bool>) -> Option<Package> {
-    let version = read_bits(bits, 3);
-    let type_id = read_bits(bits, 3);
+pub fn parse_package(bits: &mut BitReader) -> Result<Package, BitError> {
+    let version = bits.read(3)?;
+    let type_id = bits.read(3)?;
     let mut literal = None;
     let sub_packages = match type_id {
         4 => {
-            let mut literal_bits = vec![];
+            let mut value = 0;
             loop {
-                let chunk = bits.take(5).collect_vec();
-                literal_bits.extend(chunk.iter().skip(1));
-                if !chunk[0] {
+                let more = bits.read_bool()?;
+                value = value << 4 | bits.read(4)?;
+                if !more {
                     break;
                 }
             }
-            literal = Some(read_bits(&mut literal_bits.into_iter(), usize::MAX));
+            literal = Some(value);
             vec![]
         }
-        _ => match bits.next() {
-            None => return None,
-            Some(true) => {
-                let num_packages = read_bits(bits, 11);
+        _ => {
+            if bits.read_bool()? {
+                let num_packages = bits.read(11)?;
                 (0..num_packages)
-                    .map(|_| parse_package(bits).unwrap())
-                    .collect_vec()
-            }
-            Some(false) => {
-                let num_bits_sub_packages = read_bits(bits, 15) as usize;
-                let mut sub_bits = bits.take(num_bits_sub_packages).collect_vec().into_iter();
+                    .map(|_| parse_package(bits))
+                    .collect::<Result<Vec<_>, _>>()?
+            } else {
+                let num_bits_sub_packages = bits.read(15)? as usize;
+                let end = bits.position() + num_bits_sub_packages;
                 let mut packages = vec![];
-                while let Some(package) = parse_package(&mut sub_bits) {
-                    packages.push(package)
+                while bits.position() < end {
+                    packages.push(parse_package(bits)?);
                 }
                 packages
             }
-        },
+        }
     };
 
-    Some(Package {
+    Ok(Package {
         version,
         type_id,
         literal,
@@ -144,8 +128,8 @@ pub fn parse_package(bits: &mut dyn Iterator<Item = bool>) -> Option<Package> {
     })
 }
 
-pub fn solve_part1(bits: Input) -> u64 {
-    let root = parse_package(&mut bits.into_iter()).unwrap();
+pub fn solve_part1(mut bits: Input) -> u64 {
+    let root = parse_package(&mut bits).unwrap();
     let mut packages = vec![];
 
     let mut to_process = vec![root];
@@ -156,8 +140,8 @@ pub fn solve_part1(bits: Input) -> u64 {
     packages.iter().map(|package| package.version).sum::<u64>()
 }
 
-pub fn solve_part2(bits: Input) -> u64 {
-    parse_package(&mut bits.into_iter()).unwrap().operator()
+pub fn solve_part2(mut bits: Input) -> u64 {
+    parse_package(&mut bits).unwrap().operator()
 }
 
 #[cfg(test)]
@@ -165,35 +149,27 @@ mod tests {
     use super::*;
     use test_log::test;
 
-    fn to_bool_vec(string: &str) -> Vec<bool> {
-        string
-            .chars()
-            .into_iter()
-            .map(|c| c.to_digit(2).unwrap() != 0)
-            .collect_vec()
-    }
-
     #[test]
     fn example1() {
-        let input = parse("D2FE28");
-        assert_eq!(input, to_bool_vec("110100101111111000101000"));
+        let mut input = parse("D2FE28");
         assert_eq!(
-            parse_package(&mut input.into_iter())
-                .unwrap()
-                .literal
-                .unwrap(),
-            2021
+            input,
+            BitReader::from_binary("110100101111111000101000").unwrap()
         );
+        assert_eq!(parse_package(&mut input).unwrap().literal.unwrap(), 2021);
+        // Three bits of padding are left after the package
+        assert_eq!(input.remaining(), 3);
     }
 
     #[test]
     fn example2() {
-        let input = parse("38006F45291200");
+        let mut input = parse("38006F45291200");
         assert_eq!(
             input,
-            to_bool_vec("00111000000000000110111101000101001010010001001000000000")
+            BitReader::from_binary("00111000000000000110111101000101001010010001001000000000")
+                .unwrap()
         );
-        let package = parse_package(&mut input.into_iter()).unwrap();
+        let package = parse_package(&mut input).unwrap();
         assert_eq!(package.version, 1);
         assert_eq!(package.type_id, 6);
         assert_eq!(package.sub_packages.len(), 2);
@@ -202,12 +178,13 @@ mod tests {
     }
     #[test]
     fn example3() {
-        let input = parse("EE00D40C823060");
+        let mut input = parse("EE00D40C823060");
         assert_eq!(
             input,
-            to_bool_vec("11101110000000001101010000001100100000100011000001100000")
+            BitReader::from_binary("11101110000000001101010000001100100000100011000001100000")
+                .unwrap()
         );
-        let package = parse_package(&mut input.into_iter()).unwrap();
+        let package = parse_package(&mut input).unwrap();
         assert_eq!(package.version, 7);
         assert_eq!(package.type_id, 3);
         assert_eq!(package.sub_packages.len(), 3);
@@ -216,6 +193,18 @@ mod tests {
         assert_eq!(package.sub_packages[2].literal, Some(3));
     }
 
+    #[test]
+    fn truncated() {
+        let mut input = parse("D2FE");
+        assert_eq!(
+            parse_package(&mut input).err(),
+            Some(BitError::OutOfBits {
+                requested: 1,
+                remaining: 0
+            })
+        );
+    }
+
     #[test]
     fn examples_part1() {
         assert_eq!(solve_part1(parse("8A004A801A8002F478")), 16);
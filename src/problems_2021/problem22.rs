@@ -1,7 +1,9 @@
-use std::{collections::HashSet, num::ParseIntError, ops::Range};
+use std::{num::ParseIntError, ops::Range};
 
 use itertools::Itertools;
-use log::{debug, info};
+use log::info;
+
+use crate::util::boxes::{signed_union_volume, Cuboid};
 
 macro_rules! INPUT_PATH {
     () => {
@@ -53,128 +55,28 @@ pub fn parse(input: &str) -> Input {
         .collect_vec()
 }
 
-pub fn solve_part1(input: Input) -> usize {
-    let valid_x_range = -50..50 + 1;
-    let valid_y_range = -50..50 + 1;
-    let valid_z_range = -50..50 + 1;
-    let mut points = HashSet::<(i32, i32, i32)>::new();
-
-    let total_lines = input.len();
-    for (line, (on, x_range, y_range, z_range)) in input.into_iter().enumerate() {
-        info!("Line {}/{}", line, total_lines);
-        for x in x_range {
-            if !valid_x_range.contains(&x) {
-                continue;
-            }
-            for y in y_range.clone() {
-                if !valid_y_range.contains(&y) {
-                    continue;
-                }
-                for z in z_range.clone() {
-                    if !valid_z_range.contains(&z) {
-                        continue;
-                    }
-                    if on {
-                        points.insert((x, y, z));
-                    } else {
-                        points.remove(&(x, y, z));
-                    }
-                }
-            }
-        }
-    }
-    points.len()
-}
-
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-struct Cube {
-    x_range: MyRange,
-    y_range: MyRange,
-    z_range: MyRange,
+fn to_box(x_range: &MyRange, y_range: &MyRange, z_range: &MyRange) -> Cuboid<3> {
+    Cuboid::new(
+        [x_range.start, y_range.start, z_range.start].map(i64::from),
+        [x_range.end, y_range.end, z_range.end].map(i64::from),
+    )
 }
 
-impl Cube {
-    fn area(&self) -> usize {
-        self.x_range.len() * self.y_range.len() * self.z_range.len()
-    }
-
-    fn range_intersection(lhs: &MyRange, rhs: &MyRange) -> Option<MyRange> {
-        if (lhs.end.max(rhs.end) - lhs.start.min(rhs.start)) as usize >= (lhs.len() + rhs.len()) {
-            None
-        } else {
-            Some(lhs.start.max(rhs.start)..lhs.end.min(rhs.end))
-        }
-    }
-
-    fn intersect(&self, other: &Cube) -> Option<Cube> {
-        if let Some(x_intersection) = Self::range_intersection(&self.x_range, &other.x_range) {
-            if let Some(y_intersection) = Self::range_intersection(&self.y_range, &other.y_range) {
-                if let Some(z_intersection) =
-                    Self::range_intersection(&self.z_range, &other.z_range)
-                {
-                    return Some(Cube {
-                        x_range: x_intersection,
-                        y_range: y_intersection,
-                        z_range: z_intersection,
-                    });
-                }
-            }
-        }
-        None
-    }
+pub fn solve_part1(input: Input) -> usize {
+    let region = Cuboid::from_inclusive([-50; 3], [50; 3]);
+    signed_union_volume(input.iter().filter_map(|(on, x_range, y_range, z_range)| {
+        to_box(x_range, y_range, z_range)
+            .intersect(&region)
+            .map(|cuboid| (*on, cuboid))
+    })) as usize
 }
 
 pub fn solve_part2(input: Input) -> i64 {
-    let mut cubes: Vec<Cube> = vec![];
-    let mut negative_cubes: Vec<Cube> = vec![];
-    let mut sum = 0;
-    let total_lines = input.len();
-    for (line, (on, x_range, y_range, z_range)) in input.into_iter().enumerate() {
-        let new_cube = Cube {
-            x_range,
-            y_range,
-            z_range,
-        };
-        let mut intersections = vec![];
-        for old_cube in &cubes {
-            if let Some(intersection) = old_cube.intersect(&new_cube) {
-                intersections.push(intersection);
-            }
-        }
-        let mut negative_intersections = vec![];
-        for old_cube in &negative_cubes {
-            if let Some(intersection) = old_cube.intersect(&new_cube) {
-                negative_intersections.push(intersection);
-            }
-        }
-        if on {
-            sum += new_cube.area() as i64;
-            cubes.push(new_cube);
-        } else {
-            negative_cubes.push(new_cube);
-        }
-        info!("Line {}/{}, Current sum {}", line + 1, total_lines, sum);
-
-        let mut add = -1;
-        while !intersections.is_empty() {
-            debug!("Intersections {}, sum {}", intersections.len(), sum);
-            dbg!(intersections.clone());
-            sum += add * intersections.iter().map(|cube| cube.area()).sum::<usize>() as i64;
-            debug!("sum {}", sum);
-            let mut next_intersections = HashSet::new();
-            for i in 0..intersections.len() {
-                for j in i + 1..intersections.len() {
-                    if let Some(intersection) = intersections[i].intersect(&intersections[j]) {
-                        next_intersections.insert(intersection);
-                    }
-                }
-            }
-
-            intersections = next_intersections.into_iter().collect_vec();
-            add *= -1;
-        }
-    }
-    sum
+    signed_union_volume(
+        input
+            .iter()
+            .map(|(on, x_range, y_range, z_range)| (*on, to_box(x_range, y_range, z_range))),
+    ) as i64
 }
 
 #[cfg(test)]
@@ -192,10 +94,10 @@ mod tests {
 
     #[test]
     fn example1_2() {
-        /*assert_eq!(
+        assert_eq!(
             solve_part2(parse(include_str!("problem22/example1.txt"))),
             39
-        );*/
+        );
     }
 
     #[test]
@@ -208,10 +110,10 @@ mod tests {
 
     #[test]
     fn example3() {
-        /*assert_eq!(
+        assert_eq!(
             solve_part2(parse(include_str!("problem22/example3.txt"))),
             2758514936282235
-        );*/
+        );
     }
 
     #[test]
@@ -237,11 +139,14 @@ mod tests {
 
     #[test]
     fn part1() {
-        //assert_eq!(solve_part1(parse(include_str!(INPUT_PATH!()))), 596989);
+        assert_eq!(solve_part1(parse(include_str!(INPUT_PATH!()))), 596989);
     }
 
     #[test]
     fn part2() {
-        //assert_eq!(solve_part2(parse(include_str!(INPUT_PATH!()))), 0);
+        assert_eq!(
+            solve_part2(parse(include_str!(INPUT_PATH!()))),
+            1160011199157381
+        );
     }
 }
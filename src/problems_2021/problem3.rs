@@ -15,7 +15,7 @@ pub fn parse_input(path_to_input: &str) -> Vec<Vec<bool>> {
         })
         .collect()
 }
-pub fn solve_part1(input: &[Vec<bool>]) -> i32 {
+pub fn solve_part1(input: &[Vec<bool>]) -> u64 {
     let num_bits = input[0].len();
     let mut counts = vec![0; num_bits];
     input.iter().for_each(|line| {
@@ -45,7 +45,7 @@ fn most_common_bit(vector: &[Vec<bool>], pos: usize) -> bool {
         < 0
 }
 
-pub fn solve_part2(input: &[Vec<bool>]) -> i32 {
+pub fn solve_part2(input: &[Vec<bool>]) -> u64 {
     let num_bits = input[0].len();
     let mut tmp = input.to_vec();
     for pos in 0..num_bits {
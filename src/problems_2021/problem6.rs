@@ -1,7 +1,7 @@
 #![allow(clippy::ptr_arg)]
 use itertools::Itertools;
 
-use crate::util::parse::read_lines;
+use crate::util::{matrix::CountTransitions, parse::read_lines};
 
 pub static INPUT_PATH: &str = "src/problems_2021/problem6/input.txt";
 
@@ -14,33 +14,28 @@ pub fn parse_input(path_to_input: &str) -> Input {
         .collect_vec()
 }
 
-pub fn solve_part1(input: &Input) -> i32 {
-    let mut input_copy = input.clone();
-    let days = 80;
-    for _ in 0..days {
-        for index in 0..input_copy.len() {
-            let fish = &mut input_copy[index];
-            *fish -= 1;
-            if *fish == -1 {
-                *fish = 6;
-                input_copy.push(8);
-            }
-        }
-    }
+// Fish are counted per timer value, a fish with timer 0 resets to 6 and spawns
+// a new one with timer 8.
+pub fn population_after(input: &Input, days: u64) -> u64 {
+    let timers = CountTransitions::new(0..=8, |&timer| match timer {
+        0 => vec![(6, 1), (8, 1)],
+        _ => vec![(timer - 1, 1)],
+    });
+    let counts = input
+        .iter()
+        .counts()
+        .into_iter()
+        .map(|(&timer, count)| (timer, count as u64))
+        .collect();
+    timers.after(&counts, days).values().sum()
+}
 
-    input_copy.len() as i32
+pub fn solve_part1(input: &Input) -> u64 {
+    population_after(input, 80)
 }
 
 pub fn solve_part2(input: &Input) -> u64 {
-    let mut population = [0_u64; 9];
-    for fish in input {
-        population[*fish as usize] += 1;
-    }
-    for _ in 0..256 {
-        population.rotate_left(1);
-        population[6] += population[8];
-    }
-    population.iter().sum()
+    population_after(input, 256)
 }
 
 #[cfg(test)]
@@ -56,4 +51,12 @@ mod tests {
     fn part2() {
         assert_eq!(solve_part2(&parse_input(INPUT_PATH)), 1721148811504);
     }
+
+    #[test]
+    fn example() {
+        let input = vec![3, 4, 3, 1, 2];
+        assert_eq!(population_after(&input, 18), 26);
+        assert_eq!(solve_part1(&input), 5934);
+        assert_eq!(solve_part2(&input), 26984457539);
+    }
 }
@@ -1,9 +1,7 @@
-use std::collections::HashSet;
-
 use itertools::Itertools;
 use log::debug;
 
-use crate::util::parse::parse_to_vec;
+use crate::util::{components::label_components, index::expand, parse::parse_to_vec};
 
 pub static INPUT_PATH: &str = "src/problems_2021/problem9/input.txt";
 
@@ -43,58 +41,29 @@ pub fn solve_part1(input: &Input) -> i32 {
         .sum()
 }
 
-fn basin_size(height_map: &[i32], index_low_point: i32, line_width: i32) -> i32 {
-    let mut basin_indices = HashSet::<usize>::new();
-    let mut processed = HashSet::<usize>::new();
-    let mut candidates = vec![index_low_point];
-    while !candidates.is_empty() {
-        let candidate = candidates.pop().unwrap();
-        let height = height_map[candidate as usize];
-        if height == 9 || processed.contains(&(candidate as usize)) {
-            processed.insert(candidate as usize);
-            continue;
-        }
-        processed.insert(candidate as usize);
-        basin_indices.insert(candidate as usize);
-
-        let mut neighbors = vec![candidate + line_width, candidate - line_width];
-        if candidate % line_width != 0 {
-            neighbors.push(candidate - 1);
-        }
-        if candidate % line_width != line_width - 1 {
-            neighbors.push(candidate + 1);
-        }
-        for neighbor in neighbors {
-            if neighbor >= 0 && neighbor < height_map.len() as i32 {
-                candidates.push(neighbor)
-            }
-        }
-    }
-    debug!(
-        "Found Basin {:?} with size {:?} : {:?}",
-        index_low_point,
-        basin_indices.len(),
-        basin_indices
-    );
-    basin_indices.len() as i32
-}
+// Every location that is not a 9 belongs to exactly one basin
 pub fn solve_part2(input: &Input) -> i32 {
     let (line_width, height_map) = input;
-    let basins = height_map
+    let dimensions = (*line_width, height_map.len() / line_width);
+    let basins = label_components(
+        height_map
+            .iter()
+            .enumerate()
+            .filter(|(_, height)| **height != 9)
+            .map(|(index, _)| {
+                let (x, y) = expand(index as i32, &dimensions);
+                [x, y]
+            }),
+    );
+    debug!("Basins {:?}", basins.sizes);
+    basins
+        .sizes
         .iter()
-        .enumerate()
-        .filter_map(|(index, _)| {
-            if low_point(height_map, index as i32, *line_width as i32) {
-                Some(basin_size(height_map, index as i32, *line_width as i32))
-            } else {
-                None
-            }
-        })
         .sorted()
         .rev()
-        .collect_vec();
-    debug!("Basins {:?}", basins);
-    basins.iter().take(3).product()
+        .take(3)
+        .map(|size| *size as i32)
+        .product()
 }
 
 #[cfg(test)]
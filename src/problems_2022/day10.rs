@@ -1,5 +1,8 @@
 use log::info;
 
+use crate::util::ocr::recognize;
+use crate::util::vm::{operand, parse_program, split_instruction, Flow, Machine, Stop, Vm};
+
 macro_rules! INPUT_PATH {
     () => {
         "day10/input.txt"
@@ -16,41 +19,63 @@ pub fn solve() {
     );
 }
 
-type Input = Vec<String>;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    Noop,
+    Addx(i32),
+}
 
-fn parse(input: &str) -> Input {
-    input
-        .split('\n')
-        .filter(|line| !line.is_empty())
-        .map(|line| line.trim().to_string())
-        .collect()
+#[derive(Debug)]
+struct Cpu {
+    x: i32,
 }
-fn parse_addx(instruction: &str) -> i32 {
-    instruction
-        .split_whitespace()
-        .nth(1)
-        .unwrap()
-        .parse()
-        .expect("A number")
+
+impl Machine for Cpu {
+    type Instruction = Instruction;
+
+    fn parse_instruction(line: &str) -> Result<Instruction, String> {
+        match split_instruction(line) {
+            ("noop", _) => Ok(Instruction::Noop),
+            ("addx", operands) => Ok(Instruction::Addx(operand(&operands, 0)?)),
+            _ => Err(format!("Not a valid instruction {}", line)),
+        }
+    }
+
+    fn cycles(instruction: &Instruction) -> usize {
+        match instruction {
+            Instruction::Noop => 1,
+            Instruction::Addx(_) => 2,
+        }
+    }
+
+    fn execute(&mut self, instruction: &Instruction) -> Result<Flow, String> {
+        if let Instruction::Addx(value) = instruction {
+            self.x += value;
+        }
+        Ok(Flow::Next)
+    }
 }
 
-fn compute_register_values(input: Input) -> Vec<i32> {
-    let mut register = 1;
-    let mut values = vec![1, 1];
-    values.extend(input.into_iter().flat_map(|instruction| {
-        let mut register_values = vec![];
-        match instruction.as_str() {
-            "noop" => register_values.push(register),
-            i if i.starts_with("addx") => {
-                register_values.push(register);
-                register += parse_addx(i);
-                register_values.push(register);
-            }
-            unmatched => panic!("Not a valid instruction {}", unmatched),
-        };
-        register_values
-    }));
+type Input = Vec<Instruction>;
+
+fn parse(input: &str) -> Input {
+    parse_program::<Cpu>(input).unwrap_or_else(|error| panic!("{}", error))
+}
 
+// Value of the register during every cycle, index 0 is the start and the last
+// one the value after the program ended.
+fn compute_register_values(input: Input) -> Vec<i32> {
+    let total_cycles = input.iter().map(Cpu::cycles).sum::<usize>();
+    let mut vm = Vm::new(Cpu { x: 1 }, &input).breakpoint_cycles(1..=total_cycles);
+    let mut values = vec![vm.machine().x];
+    loop {
+        match vm.run() {
+            Stop::CycleBreakpoint(_) => values.push(vm.machine().x),
+            Stop::Halted => break,
+            stop => panic!("Unexpected stop {:?}", stop),
+        }
+    }
+    values.push(vm.machine().x);
     values
 }
 
@@ -70,7 +95,7 @@ fn solve_part1(input: Input) -> i32 {
         .sum()
 }
 
-fn solve_part2(input: Input) -> String {
+fn render_screen(input: Input) -> String {
     let screen = compute_register_values(input)
         .into_iter()
         .skip(1)
@@ -94,6 +119,10 @@ fn solve_part2(input: Input) -> String {
     screen
 }
 
+fn solve_part2(input: Input) -> String {
+    recognize(&render_screen(input)).expect("Screen should show capital letters")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,15 +147,16 @@ mod tests {
     #[test]
     fn example_1_2() {
         assert_eq!(
-            solve_part2(parse(include_str!(EXAMPLE_PATH!()))),
+            render_screen(parse(include_str!(EXAMPLE_PATH!()))),
             "##..##..##..##..##..##..##..##..##..##..\n###...###...###...###...###...###...###.\n####....####....####....####....####....\n#####.....#####.....#####.....#####.....\n######......######......######......####\n#######.......#######.......#######....."
         );
     }
 
     #[test]
     fn part2() {
+        assert_eq!(solve_part2(parse(include_str!(INPUT_PATH!()))), "EALGULPG");
         assert_eq!(
-            solve_part2(parse(include_str!(INPUT_PATH!()))),
+            render_screen(parse(include_str!(INPUT_PATH!()))),
             "####..##..#.....##..#..#.#....###...##..\n#....#..#.#....#..#.#..#.#....#..#.#..#.\n###..#..#.#....#....#..#.#....#..#.#....\n#....####.#....#.##.#..#.#....###..#.##.\n#....#..#.#....#..#.#..#.#....#....#..#.\n####.#..#.####..###..##..####.#.....###."
         );
     }
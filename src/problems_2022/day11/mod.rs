@@ -1,6 +1,7 @@
 use itertools::Itertools;
 use log::info;
-use primes::is_prime;
+
+use crate::util::modular::lcm_all;
 
 macro_rules! INPUT_PATH {
     () => {
@@ -18,8 +19,6 @@ pub fn solve() {
     );
 }
 
-const MAX: usize = 23 * 19 * 17 * 13 * 11 * 7 * 5 * 3 * 2;
-
 #[derive(Debug, Clone)]
 struct Item {
     number: usize,
@@ -36,7 +35,6 @@ impl Item {
 
     fn mult(&mut self, number: usize) {
         self.number *= number;
-        self.normalize();
     }
 
     fn add(&mut self, number: usize) {
@@ -45,7 +43,6 @@ impl Item {
 
     fn square(&mut self) {
         self.number = self.number.pow(2);
-        self.normalize();
     }
 
     fn div(&mut self, number: usize) {
@@ -54,8 +51,8 @@ impl Item {
         }
     }
 
-    fn normalize(&mut self) {
-        self.number %= MAX;
+    fn normalize(&mut self, modulus: usize) {
+        self.number %= modulus;
     }
 }
 
@@ -91,7 +88,7 @@ impl Op {
 }
 
 impl Monkey {
-    fn inspect(&self, mut item: Item, relief: usize) -> Item {
+    fn inspect(&self, mut item: Item, relief: usize, modulus: usize) -> Item {
         match self.op.1.clone() {
             Operant::Number(number) => match self.op.0 {
                 Op::Mult => item.mult(number),
@@ -102,6 +99,7 @@ impl Monkey {
                 item.square();
             }
         };
+        item.normalize(modulus);
         item.div(relief);
         item
     }
@@ -172,10 +170,6 @@ fn parse(input: &str) -> Input {
                 .expect("to exist")
                 .trim()
                 .parse::<usize>()
-                .map(|number| {
-                    assert!(is_prime(number as u64));
-                    number
-                })
                 .expect("a number");
             let true_target_monkey = lines
                 .next()
@@ -209,11 +203,15 @@ fn parse(input: &str) -> Input {
 }
 
 fn play_rounds(rounds: usize, mut monkeys: Input, relief: usize) -> usize {
+    // Every test only looks at the worry level modulo its divisor, so levels can be
+    // kept modulo the lcm of all divisors. Taking the relief factor into the
+    // modulus keeps the division by the relief consistent as well.
+    let modulus = lcm_all(monkeys.iter().map(|monkey| monkey.test)) * relief;
     for _ in 0..rounds {
         for monkey_index in 0..monkeys.len() {
             let monkey = monkeys[monkey_index].clone();
             for item in monkeys[monkey_index].give_items() {
-                let inspected_item = monkey.inspect(item, relief);
+                let inspected_item = monkey.inspect(item, relief, modulus);
                 monkeys[monkey.test(&inspected_item)]
                     .items
                     .push(inspected_item);
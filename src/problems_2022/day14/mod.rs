@@ -1,11 +1,10 @@
-use std::{
-    cmp::{max, min},
-    collections::HashSet,
-};
+use std::collections::HashSet;
 
 use itertools::Itertools;
 use log::info;
 
+use crate::util::line::{is_axis_aligned, polyline_points};
+
 macro_rules! INPUT_PATH {
     () => {
         "input.txt"
@@ -25,10 +24,14 @@ type Point = (i32, i32);
 type Input = HashSet<Point>;
 
 fn parse(input: &str) -> Input {
+    rock_paths(&parse_paths(input))
+}
+
+fn parse_paths(input: &str) -> Vec<Vec<Point>> {
     input
         .split('\n')
         .filter(|line| !line.is_empty())
-        .flat_map(|line| {
+        .map(|line| {
             line.split("->")
                 .map(|points| {
                     points
@@ -37,12 +40,20 @@ fn parse(input: &str) -> Input {
                         .collect_tuple::<Point>()
                         .expect("Should have x and y coordinates")
                 })
+                .collect_vec()
+        })
+        .collect()
+}
+
+fn rock_paths(paths: &[Vec<Point>]) -> Input {
+    paths
+        .iter()
+        .flat_map(|path| {
+            assert!(path
+                .iter()
                 .tuple_windows()
-                .flat_map(|(a, b)| {
-                    assert!(a.0 == b.0 || a.1 == b.1);
-                    (min(a.0, b.0)..=max(a.0, b.0))
-                        .flat_map(move |i| (min(a.1, b.1)..=max(a.1, b.1)).map(move |j| (i, j)))
-                })
+                .all(|(a, b)| is_axis_aligned(a, b)));
+            polyline_points(path)
         })
         .collect()
 }
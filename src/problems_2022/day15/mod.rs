@@ -1,9 +1,9 @@
-use std::cmp::max;
-
 use itertools::Itertools;
 use log::info;
 use regex::Regex;
 
+use crate::util::interval::IntervalSet;
+
 macro_rules! INPUT_PATH {
     () => {
         "input.txt"
@@ -59,52 +59,33 @@ fn manhattan_distance(lhs: &Point, rhs: &Point) -> usize {
     ((lhs.0 - rhs.0).abs() + (lhs.1 - rhs.1).abs()) as usize
 }
 
-type Interval = (i32, i32);
-fn compute_intervals(input: &Input, row: i32) -> Vec<Interval> {
-    input
-        .iter()
-        .filter_map(|(sensor, beacon)| {
-            let distance = manhattan_distance(sensor, beacon);
-            match distance as i32 - (row - sensor.1).abs() {
-                width if width > 0 => Some((sensor.0 - width, sensor.0 + width)),
-                _ => None,
-            }
-        })
-        .sorted()
-        .rev()
-        .collect()
+// Positions in `row` covered by at least one sensor
+fn compute_intervals(input: &Input, row: i32) -> IntervalSet<i32> {
+    IntervalSet::from_inclusive(input.iter().filter_map(|(sensor, beacon)| {
+        let distance = manhattan_distance(sensor, beacon);
+        match distance as i32 - (row - sensor.1).abs() {
+            width if width >= 0 => Some((sensor.0 - width, sensor.0 + width)),
+            _ => None,
+        }
+    }))
 }
 
 fn solve_part1(input: Input, row: i32) -> usize {
-    let mut visible_positions = 0;
-    let mut intervals = compute_intervals(&input, row);
-
-    let mut interval = intervals.pop().unwrap();
-    for next_interval in intervals.into_iter().rev() {
-        if interval.1 >= next_interval.0 {
-            interval.1 = max(interval.1, next_interval.1)
-        } else {
-            visible_positions += interval.1 - interval.0;
-            interval = next_interval;
-        }
-    }
-
-    (interval.1 - interval.0 + visible_positions) as usize
+    let covered = compute_intervals(&input, row);
+    let beacons = input
+        .iter()
+        .filter(|(_, beacon)| beacon.1 == row && covered.contains(beacon.0))
+        .map(|(_, beacon)| beacon.0)
+        .unique()
+        .count();
+    covered.len() as usize - beacons
 }
 
 fn solve_part2(input: Input, length: i32) -> usize {
+    let search_area = IntervalSet::from_inclusive([(0, length)]);
     for row in 0..=length {
-        if let Err(x) = std::iter::once((length + 1, i32::MAX))
-            .chain(compute_intervals(&input, row).into_iter())
-            .rev()
-            .try_fold(0, |x, next_interval| {
-                if x + 1 < next_interval.0 {
-                    Err(x + 1)
-                } else {
-                    Ok(max(x, next_interval.1))
-                }
-            })
-        {
+        let uncovered = search_area.difference(&compute_intervals(&input, row));
+        if let Some(x) = uncovered.min() {
             info!("Found: {:?}", (x, row));
             return x as usize * 4000000 + row as usize;
         }
@@ -1,12 +1,17 @@
 use std::{
-    cmp::{max, min},
+    cmp::{max, Reverse},
     collections::HashMap,
 };
 
 use itertools::Itertools;
-use log::info;
+use log::{debug, info};
 use regex::Regex;
 
+use crate::util::{
+    search::Memo,
+    shortest_path::{contract, Edge},
+};
+
 macro_rules! INPUT_PATH {
     () => {
         "input.txt"
@@ -45,34 +50,30 @@ type Cost = usize;
 type Graph = HashMap<Room, (Flow, HashMap<Room, Cost>)>;
 
 fn cleanup_graph(graph: Input) -> Graph {
-    let mut graph_with_costs = graph
-        .into_iter()
-        .map(|(key, value)| {
-            let new_value = (
-                value.0,
-                value.1.into_iter().map(|neighbor| (neighbor, 1)).collect(),
-            );
-            (key, new_value)
-        })
-        .collect::<Graph>();
-
-    while let Some((room, (_, neighbors))) = graph_with_costs
+    let adj_list = graph
         .iter()
-        .find(|(_, value)| value.0 == 0)
-        .map(|(key, value)| (*key, value.clone()))
-    {
-        for (neighbor, costs) in neighbors.iter() {
-            for (other_neighbor, other_costs) in neighbors.iter() {
-                if let Some((_, neighbors)) = graph_with_costs.get_mut(neighbor) {
-                    let new_costs = neighbors.entry(*other_neighbor).or_insert(Cost::MAX);
-                    *new_costs = min(*new_costs, *costs + *other_costs);
-                }
-            }
-        }
-        graph_with_costs.remove(&room);
-    }
+        .map(|(room, (_, neighbors))| {
+            let edges = neighbors
+                .iter()
+                .map(|neighbor| Edge {
+                    node: *neighbor,
+                    cost: 1,
+                })
+                .collect_vec();
+            (*room, edges)
+        })
+        .collect::<HashMap<_, _>>();
 
-    graph_with_costs
+    contract(&adj_list, |room| *room == START || graph[room].0 > 0)
+        .into_iter()
+        .map(|(room, edges)| {
+            let costs = edges
+                .into_iter()
+                .map(|edge| (edge.node, edge.cost as Cost))
+                .collect();
+            (room, (graph[&room].0, costs))
+        })
+        .collect()
 }
 
 type Flow = usize;
@@ -81,6 +82,7 @@ fn to_name(name: &str) -> Room {
     name.chars().collect_tuple().expect("Should be two chars")
 }
 type Room = (char, char);
+const START: Room = ('A', 'A');
 type Time = i64;
 
 fn max_flow(
@@ -88,116 +90,99 @@ fn max_flow(
     room: Room,
     time: Time,
     opened: &Vec<Room>,
-    cache: &mut HashMap<(Room, Time, Vec<Room>), Flow>,
+    memo: &mut Memo<(Room, Time, Vec<Room>), Flow>,
 ) -> usize {
     if time <= 0 || opened.len() == graph.len() {
         return 0;
     }
-    let cache_key = (room, time, opened.clone());
-    if let Some(flow) = cache.get(&cache_key) {
-        return *flow;
-    }
-    let (flow_rate, neighbors) = graph.get(&room).unwrap();
+    memo.get_or_compute((room, time, opened.clone()), |memo| {
+        let (flow_rate, neighbors) = graph.get(&room).unwrap();
 
-    let without_open = neighbors
-        .iter()
-        .map(|neighbor| max_flow(graph, *neighbor, time - 1, opened, cache))
-        .max()
-        .unwrap();
-
-    let next_opened = opened
-        .iter()
-        .cloned()
-        .chain([room].into_iter())
-        .sorted()
-        .collect_vec();
+        let without_open = neighbors
+            .iter()
+            .map(|neighbor| max_flow(graph, *neighbor, time - 1, opened, memo))
+            .max()
+            .unwrap();
 
-    let with_open = if *flow_rate > 0 && !opened.contains(&room) {
-        (time - 1) as usize * flow_rate + max_flow(graph, room, time - 1, &next_opened, cache)
-    } else {
-        0
-    };
+        let next_opened = opened
+            .iter()
+            .cloned()
+            .chain([room].into_iter())
+            .sorted()
+            .collect_vec();
+
+        let with_open = if *flow_rate > 0 && !opened.contains(&room) {
+            (time - 1) as usize * flow_rate + max_flow(graph, room, time - 1, &next_opened, memo)
+        } else {
+            0
+        };
 
-    let flow = max(without_open, with_open);
-    cache.insert(cache_key, flow);
-    flow
+        max(without_open, with_open)
+    })
 }
 
 fn solve_part1(input: Input) -> usize {
-    max_flow(&input, ('A', 'A'), 30, &vec![], &mut HashMap::new())
+    let mut memo = Memo::new();
+    let flow = max_flow(&input, START, 30, &vec![], &mut memo);
+    debug!("{:?}", memo.stats);
+    flow
 }
 
-fn max_flow_vec(
+// Best total flow for every set of valves that can be opened in time, walking the contracted
+// graph. Moving to a valve costs the length of the path to it, opening it one more minute.
+fn best_flows(
     graph: &Graph,
-    rooms: (Room, Room),
+    room: Room,
     time: Time,
-    opened: &Vec<Room>,
-    cache: &mut HashMap<((Room, Room), Time, Vec<Room>), Flow>,
-) -> usize {
-    if time <= 0 || opened.len() == graph.len() {
-        return 0;
-    }
-    let cache_key = (rooms, time, opened.clone());
-    if let Some(flow) = cache.get(&cache_key) {
-        return *flow;
+    opened: Vec<Room>,
+    flow: Flow,
+    best: &mut HashMap<Vec<Room>, Flow>,
+) {
+    let entry = best.entry(opened.clone()).or_insert(0);
+    *entry = max(*entry, flow);
+
+    for (next, cost) in graph[&room].1.iter() {
+        let remaining = time - *cost as Time - 1;
+        if remaining <= 0 || graph[next].0 == 0 || opened.contains(next) {
+            continue;
+        }
+        let next_opened = opened
+            .iter()
+            .cloned()
+            .chain([*next].into_iter())
+            .sorted()
+            .collect_vec();
+        let next_flow = flow + remaining as Flow * graph[next].0;
+        best_flows(graph, *next, remaining, next_opened, next_flow, best);
     }
+}
 
-    let mut flow = 0;
-    let (flow_rate_1, neighbors_1) = graph.get(&rooms.0).unwrap();
-    let (flow_rate_2, neighbors_2) = graph.get(&rooms.1).unwrap();
+// Me and the elephant open disjoint sets of valves, so the best pair of disjoint sets wins.
+fn solve_part2(input: Input) -> usize {
+    let mut best = HashMap::new();
+    best_flows(&cleanup_graph(input), START, 26, vec![], 0, &mut best);
 
-    for (neighbor_1, _) in neighbors_1.iter()
-    /*.chain([(rooms.0, 1)].iter()) */
-    {
-        if *neighbor_1 == rooms.0 && (*flow_rate_1 == 0 || opened.contains(&rooms.0)) {
-            continue;
+    let best = best
+        .into_iter()
+        .sorted_by_key(|(_, flow)| Reverse(*flow))
+        .collect_vec();
+    let mut flow = 0;
+    for (i, (mine, my_flow)) in best.iter().enumerate() {
+        if my_flow * 2 < flow {
+            break;
         }
-
-        for (neighbor_2, _) in neighbors_2.iter()
-        /*.chain([rooms.1].iter()) */
-        {
-            let mut flow_inner = 0;
-            let mut opened_new = opened.clone();
-            if *neighbor_1 == rooms.0 {
-                opened_new.push(rooms.0);
-                flow_inner += (time - 1) as usize * flow_rate_1;
+        for (elephants, elephant_flow) in best[i..].iter() {
+            if my_flow + elephant_flow <= flow {
+                break;
             }
-            if *neighbor_2 == rooms.1 {
-                if *flow_rate_2 == 0 || opened_new.contains(&rooms.1) {
-                    continue;
-                }
-                opened_new.push(rooms.1);
-                flow_inner += (time - 1) as usize * flow_rate_2;
+            if !mine.iter().any(|room| elephants.contains(room)) {
+                flow = my_flow + elephant_flow;
             }
-            flow_inner += max_flow_vec(
-                graph,
-                [*neighbor_1, *neighbor_2]
-                    .into_iter()
-                    .sorted()
-                    .collect_tuple()
-                    .unwrap(),
-                time - 1,
-                &opened_new,
-                cache,
-            );
-            flow = max(flow, flow_inner);
         }
     }
-
-    cache.insert(cache_key, flow);
     flow
 }
 
-fn solve_part2(input: Input) -> usize {
-    max_flow_vec(
-        &cleanup_graph(input),
-        (('A', 'A'), ('A', 'A')),
-        26,
-        &vec![],
-        &mut HashMap::new(),
-    )
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -227,6 +212,6 @@ mod tests {
 
     #[test]
     fn part2() {
-        assert_eq!(solve_part2(parse(include_str!(INPUT_PATH!()))), 0);
+        assert_eq!(solve_part2(parse(include_str!(INPUT_PATH!()))), 2675);
     }
 }
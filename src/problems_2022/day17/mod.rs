@@ -1,12 +1,16 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::HashSet,
+    io::{self, Write},
     time::SystemTime,
 };
 
 use itertools::Itertools;
 use log::{debug, info};
 
-use crate::util::draw::grid_to_string;
+use crate::util::{
+    cycle::{detect_cycle, extrapolate},
+    draw::{Animator, GridRenderer},
+};
 
 macro_rules! INPUT_PATH {
     () => {
@@ -204,16 +208,6 @@ impl Rock {
     fn max_y(&self) -> i64 {
         self.kind.max_y() + self.origin.1
     }
-
-    fn all_kinds() -> Vec<Rock> {
-        Kind::all_kinds()
-            .into_iter()
-            .map(|kind| Rock {
-                origin: (0, 0),
-                kind,
-            })
-            .collect()
-    }
 }
 
 fn remove_old_rocks(chamber: &mut HashSet<Point>, cutoff: i64, rock_index: usize) {
@@ -223,15 +217,26 @@ fn remove_old_rocks(chamber: &mut HashSet<Point>, cutoff: i64, rock_index: usize
     }
 }
 
-fn print_chamber(chamber: &HashSet<Point>, rock: Option<Rock>) {
-    let mut rocks = chamber.clone();
-    if let Some(rock) = rock {
-        rock.compute_points().into_iter().for_each(|point| {
-            rocks.insert(point);
-        });
-    }
+fn chamber_frame(chamber: &HashSet<Point>, rock: Rock) -> String {
+    GridRenderer::new()
+        .marker('@', rock.compute_points())
+        .render_points(&chamber.iter().copied().collect_vec())
+}
 
-    info!("\n{}", grid_to_string(&rocks.into_iter().collect_vec()));
+// Shows the chamber every time a rock comes to rest, with that rock marked, until
+// `max_rock` rocks are dropped or the animation is stopped.
+fn animate_rocks<W: Write>(
+    tower: &mut Tower,
+    max_rock: usize,
+    animator: &mut Animator<W>,
+) -> io::Result<usize> {
+    animator.run(|| {
+        if tower.rock_index >= max_rock {
+            return None;
+        }
+        let rock = tower.drop_next_rock();
+        Some(chamber_frame(&tower.chamber, rock))
+    })
 }
 
 fn update_height_profile(
@@ -255,51 +260,86 @@ fn update_height_profile(
     )
 }
 
-fn simulate_rocks(input: Input, max_rock: usize) -> i64 {
-    type CacheKey = (Vec<i64>, usize, Kind);
-    let mut cache = HashMap::<CacheKey, Rock>::new();
-    let mut chamber = HashSet::<Point>::new();
-    let mut height_profile = vec![0; 7];
-    let mut highest = -1;
-    for floor in 0..7 {
-        chamber.insert((floor, -1));
-    }
+#[derive(Debug, Clone)]
+struct Tower<'a> {
+    jets: &'a [Direction],
+    jet_index: usize,
+    rock_index: usize,
+    chamber: HashSet<Point>,
+    height_profile: Vec<i64>,
+    highest: i64,
+}
 
-    let mut jets = input.iter().enumerate().cycle();
+impl<'a> Tower<'a> {
+    fn new(jets: &'a [Direction]) -> Tower<'a> {
+        Tower {
+            jets,
+            jet_index: 0,
+            rock_index: 0,
+            chamber: (0..7).map(|floor| (floor, -1)).collect(),
+            height_profile: vec![0; 7],
+            highest: -1,
+        }
+    }
 
-    for (rock_number, mut rock) in Rock::all_kinds()
-        .iter()
-        .copied()
-        .cycle()
-        .enumerate()
-        .take(max_rock)
-    {
-        let jet_index = jets.clone().peekable().peek().unwrap().0;
+    fn drop_rock(&mut self) {
+        self.drop_next_rock();
+    }
 
-        let cache_key = (height_profile.clone(), jet_index, rock.kind);
-        if let Some(cached_rock) = cache.get(&cache_key) {
-            rock = cached_rock.repositioned((0, highest));
-        } else {
-            rock = rock.repositioned((2, highest + 4));
-            loop {
-                if !rock.push(*jets.next().unwrap().1, &chamber).fall(&chamber) {
-                    break;
-                }
+    // Returns the rock where it came to rest
+    fn drop_next_rock(&mut self) -> Rock {
+        let kind = Kind::all_kinds()[self.rock_index % Kind::all_kinds().len()];
+        let mut rock = Rock {
+            origin: (2, self.highest + 4),
+            kind,
+        };
+        loop {
+            let jet = self.jets[self.jet_index];
+            self.jet_index = (self.jet_index + 1) % self.jets.len();
+            if !rock.push(jet, &self.chamber).fall(&self.chamber) {
+                break;
             }
-            cache.insert(cache_key, rock.repositioned((0, -highest)));
         }
 
         let points = rock.compute_points();
-        (height_profile, highest) = update_height_profile(&height_profile, highest, &points);
-        chamber.extend(points.into_iter());
+        (self.height_profile, self.highest) =
+            update_height_profile(&self.height_profile, self.highest, &points);
+        self.chamber.extend(points.into_iter());
 
-        if rock_number % 100 == 0 {
-            remove_old_rocks(&mut chamber, highest - 100, rock_number);
+        if self.rock_index % 100 == 0 {
+            remove_old_rocks(&mut self.chamber, self.highest - 100, self.rock_index);
         }
+        self.rock_index += 1;
+        rock
+    }
+
+    fn height(&self) -> i64 {
+        self.highest + 1
     }
 
-    print_chamber(&chamber, None);
-    highest + 1
+    // The next rocks only depend on the shape of the surface, the next jet and the next rock
+    fn cache_key(&self) -> (Vec<i64>, usize, usize) {
+        (
+            self.height_profile.clone(),
+            self.jet_index,
+            self.rock_index % Kind::all_kinds().len(),
+        )
+    }
+}
+
+fn simulate_rocks(input: Input, max_rock: usize) -> i64 {
+    let tower = Tower::new(&input);
+    let cycle = detect_cycle(tower.clone(), Tower::drop_rock, Tower::cache_key, max_rock);
+    info!("Found {:?}", cycle);
+
+    match cycle {
+        Some(cycle) => extrapolate(tower, Tower::drop_rock, Tower::height, cycle, max_rock),
+        None => {
+            let mut tower = tower;
+            (0..max_rock).for_each(|_| tower.drop_rock());
+            tower.height()
+        }
+    }
 }
 
 fn solve_part1(input: Input) -> usize {
@@ -329,6 +369,27 @@ mod tests {
         assert_eq!(solve_part1(input), 3068);
     }
 
+    #[test]
+    fn example_1_animation() {
+        let input = parse(include_str!(EXAMPLE_PATH!()));
+        let mut tower = Tower::new(&input);
+        let mut animator = Animator::headless(vec![]);
+        assert_eq!(animate_rocks(&mut tower, 3, &mut animator).unwrap(), 3);
+        assert_eq!(tower.height(), 6);
+        let output = String::from_utf8(animator.into_inner()).unwrap();
+        assert!(output.starts_with("Frame 1\n..@@@@.\n#######\n"));
+        assert!(output.ends_with(
+            "Frame 3\n\
+             ..@....\n\
+             ..@....\n\
+             @@@#...\n\
+             ..###..\n\
+             ...#...\n\
+             ..####.\n\
+             #######\n"
+        ));
+    }
+
     #[test]
     fn part1() {
         assert_eq!(solve_part1(parse(include_str!(INPUT_PATH!()))), 3232);
@@ -349,6 +410,9 @@ mod tests {
 
     #[test]
     fn part2() {
-        assert_eq!(solve_part2(parse(include_str!(INPUT_PATH!()))), 0);
+        assert_eq!(
+            solve_part2(parse(include_str!(INPUT_PATH!()))),
+            1585632183915
+        );
     }
 }
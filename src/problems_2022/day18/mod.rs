@@ -1,10 +1,10 @@
-use std::{
-    collections::{HashSet},
-};
+use std::collections::HashSet;
 
-use itertools::Itertools;
+use itertools::{iproduct, Itertools};
 use log::{debug, info};
 
+use crate::util::components::label_components;
+
 macro_rules! INPUT_PATH {
     () => {
         "input.txt"
@@ -59,83 +59,33 @@ fn solve_part1(input: Input) -> usize {
         .count()
 }
 
-fn is_background(
-    cache_background: &mut HashSet<Point>,
-    cache_not_background: &mut HashSet<Point>,
-    x_range: (i32, i32),
-    y_range: (i32, i32),
-    z_range: (i32, i32),
-    point: &Point,
-) -> bool {
-    if cache_background.contains(point) {
-        return true;
-    }
-
-    if cache_not_background.contains(point) {
-        return false;
-    }
-
-    let mut queue = vec![*point];
-    let mut visited = HashSet::<Point>::new();
-    while let Some(p) = queue.pop() {
-        if cache_background.contains(&p)
-            || (p.0 < x_range.0 || p.0 > x_range.1)
-            || (p.1 < y_range.0 || p.1 > y_range.1)
-            || (p.2 < z_range.0 || p.2 > z_range.1)
-        {
-            cache_background.extend(visited);
-            return true;
-        }
-        if visited.contains(&p) || cache_not_background.contains(&p) {
-            continue;
-        }
-        visited.insert(p);
-
-        for neighbor in cube_sides(&p) {
-            queue.push(neighbor);
-        }
-    }
-    cache_not_background.extend(visited);
-    false
-}
-
+// Label all air cells in the bounding box grown by one. The corner of that box is always air,
+// its component is the exterior and every other component is an enclosed pocket.
 fn solve_part2(input: Input) -> usize {
-    let x_range = match input.iter().map(|(x, _, _)| x).minmax() {
-        itertools::MinMaxResult::NoElements => unreachable!(),
-        itertools::MinMaxResult::OneElement(_) => unreachable!(),
-        itertools::MinMaxResult::MinMax(min, max) => (*min, *max),
+    let range = |coordinate: fn(&Point) -> i32| {
+        let (min, max) = input
+            .iter()
+            .map(coordinate)
+            .minmax()
+            .into_option()
+            .expect("Droplet should not be empty");
+        min - 1..=max + 1
     };
-
-    let y_range = match input.iter().map(|(_, y, _)| y).minmax() {
-        itertools::MinMaxResult::NoElements => unreachable!(),
-        itertools::MinMaxResult::OneElement(_) => unreachable!(),
-        itertools::MinMaxResult::MinMax(min, max) => (*min, *max),
-    };
-
-    let z_range = match input.iter().map(|(_, _, z)| z).minmax() {
-        itertools::MinMaxResult::NoElements => unreachable!(),
-        itertools::MinMaxResult::OneElement(_) => unreachable!(),
-        itertools::MinMaxResult::MinMax(min, max) => (*min, *max),
-    };
-
-    let mut cache_background = HashSet::new();
-    let mut cache_not_background: HashSet<Point> = input.iter().cloned().collect();
-
+    let (x_range, y_range, z_range) = (range(|p| p.0), range(|p| p.1), range(|p| p.2));
     debug!("{:?}, {:?}, {:?}", x_range, y_range, z_range);
 
+    let air = label_components(
+        iproduct!(x_range.clone(), y_range.clone(), z_range.clone())
+            .filter(|point| !input.contains(point))
+            .map(|(x, y, z)| [x, y, z]),
+    );
+    debug!("{} air pockets", air.len() - 1);
+    let exterior = air.label(&[*x_range.start(), *y_range.start(), *z_range.start()]);
+
     input
         .iter()
         .flat_map(cube_sides)
-        .filter(|side| {
-            is_background(
-                &mut cache_background,
-                &mut cache_not_background,
-                x_range,
-                y_range,
-                z_range,
-                side,
-            )
-        })
+        .filter(|(x, y, z)| air.label(&[*x, *y, *z]) == exterior)
         .count()
 }
 
@@ -1,12 +1,14 @@
 use std::{
     cmp::{max, min},
-    collections::{HashMap, HashSet, VecDeque},
+    collections::HashMap,
 };
 
 use itertools::Itertools;
 use log::{debug, info};
 use regex::Regex;
 
+use crate::util::search::branch_and_bound;
+
 macro_rules! INPUT_PATH {
     () => {
         "input.txt"
@@ -114,11 +116,12 @@ impl Inventory {
         }
     }
 
-    fn produce(&mut self) {
-        self.ore += self.ore_robot;
-        self.clay += self.clay_robot;
-        self.obsidian += self.obsidian_robot;
-        self.geode += self.geode_robot;
+    // Robots only start producing the minute after they have been built
+    fn produce(&mut self, robots: &Inventory) {
+        self.ore += robots.ore_robot;
+        self.clay += robots.clay_robot;
+        self.obsidian += robots.obsidian_robot;
+        self.geode += robots.geode_robot;
     }
 
     fn remove(&mut self, cost: Ressource) -> bool {
@@ -161,6 +164,27 @@ impl Inventory {
         }
     }
 
+    // Wait until the robot can be built and build it. Only worth it if the robot
+    // is finished before the time runs out.
+    fn build_next(
+        &self,
+        blueprint: &Blueprint,
+        kind: Ressource,
+        time: Time,
+    ) -> Option<(Inventory, Time)> {
+        let mut inventory = self.clone();
+        for time in (2..=time).rev() {
+            let mut building = inventory.clone();
+            if building.build_robot_if_possible(blueprint, kind) {
+                building.produce(&inventory);
+                return Some((building, time - 1));
+            }
+            let robots = inventory.clone();
+            inventory.produce(&robots);
+        }
+        None
+    }
+
     fn build_robot_if_possible(&mut self, blueprint: &Blueprint, kind: Ressource) -> bool {
         match kind {
             Ressource::Ore(_) => {
@@ -195,47 +219,63 @@ impl Inventory {
 type Time = usize;
 
 fn max_geode(blueprint: Blueprint, time: usize) -> usize {
-    let mut queue = VecDeque::<(Inventory, Time)>::new();
-    queue.push_back((Inventory::new(), time));
-    let mut visited = HashSet::<(Inventory, Time)>::new();
-
-    while let Some((mut inventory, time)) = queue.pop_front() {
-        info!("{time}: {}", queue.len());
-        if time == 0 && visited.contains(&(inventory.clone(), time)) {
-            continue;
-        }
-        visited.insert((inventory.clone(), time));
-
-        inventory.produce();
-
-        queue.push_back((inventory.clone(), time - 1));
-
-        let mut clone = inventory.clone();
-        if clone.build_robot_if_possible(&blueprint, Ressource::Ore(0)) {
-            queue.push_back((clone, time - 1));
-        }
-
-        let mut clone = inventory.clone();
-        if clone.build_robot_if_possible(&blueprint, Ressource::Clay(0)) {
-            queue.push_back((clone, time - 1));
-        }
-
-        let mut clone = inventory.clone();
-        if clone.build_robot_if_possible(&blueprint, Ressource::Obsidian(0)) {
-            queue.push_back((clone, time - 1));
-        }
-
-        let mut clone = inventory.clone();
-        if clone.build_robot_if_possible(&blueprint, Ressource::Geode(0)) {
-            queue.push_back((clone, time - 1));
-        }
-    }
-
-    visited
-        .into_iter()
-        .map(|(inventory, _)| inventory.geode)
-        .max()
-        .unwrap()
+    // Building more robots of a kind than any robot costs of that ressource is never useful.
+    // Instead of simulating every minute the search jumps straight to the next robot built.
+    let costs = [
+        &blueprint.ore_robot,
+        &blueprint.clay_robot,
+        &blueprint.obsidian_robot,
+        &blueprint.geode_robot,
+    ]
+    .into_iter()
+    .flat_map(|robot| robot.cost.iter())
+    .collect_vec();
+    let max_cost = |kind: fn(&Ressource) -> Option<usize>| {
+        costs
+            .iter()
+            .filter_map(|cost| kind(cost))
+            .max()
+            .unwrap_or(0)
+    };
+    let max_ore = max_cost(|cost| match cost {
+        Ressource::Ore(amount) => Some(*amount),
+        _ => None,
+    });
+    let max_clay = max_cost(|cost| match cost {
+        Ressource::Clay(amount) => Some(*amount),
+        _ => None,
+    });
+    let max_obsidian = max_cost(|cost| match cost {
+        Ressource::Obsidian(amount) => Some(*amount),
+        _ => None,
+    });
+
+    let solution = branch_and_bound(
+        (Inventory::new(), time),
+        |(inventory, time)| {
+            [
+                (Ressource::Geode(0), true),
+                (
+                    Ressource::Obsidian(0),
+                    inventory.obsidian_robot < max_obsidian,
+                ),
+                (Ressource::Clay(0), inventory.clay_robot < max_clay),
+                (Ressource::Ore(0), inventory.ore_robot < max_ore),
+            ]
+            .into_iter()
+            .filter(|(_, useful)| *useful)
+            .filter_map(|(kind, _)| inventory.build_next(&blueprint, kind, *time))
+            .collect_vec()
+        },
+        // Geodes at the end without building anything else
+        |(inventory, time)| inventory.geode + inventory.geode_robot * time,
+        // ... plus one more geode robot in every remaining minute
+        |(inventory, time)| {
+            inventory.geode + inventory.geode_robot * time + time * time.saturating_sub(1) / 2
+        },
+    );
+    debug!("Blueprint {}: {:?}", blueprint.id, solution.stats);
+    solution.value
 }
 
 fn solve_part1(input: Input) -> usize {
@@ -268,7 +308,7 @@ mod tests {
 
     #[test]
     fn part1() {
-        assert_eq!(solve_part1(parse(include_str!(INPUT_PATH!()))), 0);
+        assert_eq!(solve_part1(parse(include_str!(INPUT_PATH!()))), 1264);
     }
 
     #[test]
@@ -4,6 +4,8 @@ use std::collections::HashMap;
 use itertools::Itertools;
 use log::info;
 
+use crate::util::graph::evaluate;
+
 macro_rules! INPUT_PATH {
     () => {
         "input.txt"
@@ -119,16 +121,35 @@ fn compute(
     Ok(result)
 }
 
+fn operands(name: &[char; 4], monkeys: &Input) -> Vec<[char; 4]> {
+    match monkeys.get(name) {
+        Some(Instruction::Op((lhs, _, rhs))) => vec![*lhs, *rhs],
+        _ => vec![],
+    }
+}
+
+// Value of every monkey that does not depend on a missing monkey
+fn evaluate_monkeys(monkeys: &Input) -> HashMap<[char; 4], i64> {
+    evaluate(
+        monkeys.keys().cloned(),
+        |name| operands(name, monkeys),
+        |name, operands: Vec<&Option<i64>>| match monkeys.get(name)? {
+            Instruction::Number(number) => Some(*number),
+            Instruction::Op((_, op, _)) => Some(op.compute((*operands[0])?, (*operands[1])?)),
+        },
+    )
+    .unwrap_or_else(|cycle| panic!("Monkeys can't depend on themselves: {}", cycle))
+    .into_iter()
+    .filter_map(|(name, number)| Some((name, number?)))
+    .collect()
+}
+
 fn solve_part1(input: Input) -> i64 {
-    compute(&['r', 'o', 'o', 't'], &input, &mut HashMap::new()).unwrap()
+    evaluate_monkeys(&input)[&['r', 'o', 'o', 't']]
 }
 
 fn precompute_cache(monkeys: Input) -> HashMap<[char; 4], i64> {
-    let mut cache = HashMap::new();
-
-    for (name, _) in monkeys.iter() {
-        let _ = compute(name, &monkeys, &mut cache);
-    }
+    let cache = evaluate_monkeys(&monkeys);
     info!("{}/{} monkeys cached", cache.len(), monkeys.len());
     cache
 }
@@ -1,8 +1,8 @@
-use std::collections::HashSet;
-
 use itertools::Itertools;
 use log::info;
 
+use crate::util::window::DistinctCounter;
+
 macro_rules! INPUT_PATH {
     () => {
         "day6/input.txt"
@@ -26,25 +26,14 @@ fn parse(input: &str) -> Input {
 }
 
 fn first_unique_sequence(input: Input, length: usize) -> usize {
-    *input
+    let mut window = DistinctCounter::new(length);
+    input
         .into_iter()
-        .enumerate()
-        .collect_vec()
-        .windows(length)
-        .find(|window| {
-            window
-                .iter()
-                .map(|(_, x)| x)
-                .cloned()
-                .collect::<HashSet<char>>()
-                .len()
-                == length
+        .position(|c| {
+            window.push(c);
+            window.is_full() && window.all_distinct()
         })
         .expect("Expected to find at least one")
-        .iter()
-        .map(|(index, _)| index)
-        .max()
-        .expect("Expected to find a max index")
         + 1
 }
 
@@ -1,7 +1,9 @@
 use std::collections::HashSet;
 
 use itertools::Itertools;
-use log::info;
+use log::{debug, info};
+
+use crate::util::draw::GridRenderer;
 
 macro_rules! INPUT_PATH {
     () => {
@@ -103,23 +105,34 @@ fn do_step(points: &mut [Point], dir: Direction, steps: i32) -> Vec<Point> {
         .collect()
 }
 
-fn solve_part1(input: Input) -> usize {
-    let mut points = vec![Point::new(0, 0); 2];
+fn tail_positions(input: Input, knots: usize) -> HashSet<Point> {
+    let mut points = vec![Point::new(0, 0); knots];
     input
         .into_iter()
         .flat_map(|(direction, steps)| do_step(&mut points, direction, steps))
-        .collect::<HashSet<Point>>()
-        .len()
+        .collect()
+}
+
+// Positions visited by the tail as '#', the start as 's'
+fn draw_visited(visited: &HashSet<Point>) -> String {
+    GridRenderer::new().marker('s', [(0, 0)]).render_points(
+        &visited
+            .iter()
+            .map(|point| (point.x as i64, point.y as i64))
+            .collect_vec(),
+    )
+}
+
+fn solve_part1(input: Input) -> usize {
+    let visited = tail_positions(input, 2);
+    debug!("\n{}", draw_visited(&visited));
+    visited.len()
 }
 
 fn solve_part2(input: Input) -> usize {
-    let mut points = vec![Point::new(0, 0); 10];
-    input
-        .into_iter()
-        .flat_map(|(direction, steps)| do_step(&mut points, direction, steps))
-        .collect::<HashSet<Point>>()
-        .into_iter()
-        .len()
+    let visited = tail_positions(input, 10);
+    debug!("\n{}", draw_visited(&visited));
+    visited.len()
 }
 
 #[cfg(test)]
@@ -138,6 +151,15 @@ mod tests {
         assert_eq!(solve_part1(parse(include_str!(EXAMPLE_PATH!()))), 13);
     }
 
+    #[test]
+    fn example_1_visited() {
+        let visited = tail_positions(parse(include_str!(EXAMPLE_PATH!())), 2);
+        assert_eq!(
+            draw_visited(&visited),
+            "..##.\n...##\n.####\n....#\ns###.\n"
+        );
+    }
+
     #[test]
     fn part1() {
         assert_eq!(solve_part1(parse(include_str!(INPUT_PATH!()))), 6057);
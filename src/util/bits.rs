@@ -0,0 +1,344 @@
+#![allow(unused)]
+use std::fmt::Display;
+
+const WORD_BITS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BitError {
+    // More bits were requested than are left in the stream
+    OutOfBits { requested: usize, remaining: usize },
+    // A single read returns a u64, so it can not be wider than 64 bits
+    TooWide(usize),
+    // Character at the given position is not a digit of the expected base
+    InvalidDigit { position: usize, digit: char },
+}
+
+impl Display for BitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BitError::OutOfBits {
+                requested,
+                remaining,
+            } => write!(
+                f,
+                "Requested {} bits but only {} are left",
+                requested, remaining
+            ),
+            BitError::TooWide(bits) => {
+                write!(
+                    f,
+                    "Can read at most {} bits at once, not {}",
+                    WORD_BITS, bits
+                )
+            }
+            BitError::InvalidDigit { position, digit } => {
+                write!(f, "Invalid digit {:?} at position {}", digit, position)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BitError {}
+
+// Low `n` bits set, n in 0..=64
+fn mask(n: usize) -> u64 {
+    if n == WORD_BITS {
+        u64::MAX
+    } else {
+        (1 << n) - 1
+    }
+}
+
+// Bits packed into words, the first bit of the stream is the most significant
+// bit of the first word. Unused bits of the last word are always zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitWriter {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> BitWriter {
+        BitWriter::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Appends the lowest `n` bits of value, most significant first.
+    pub fn write(&mut self, value: u64, n: usize) {
+        assert!(
+            n <= WORD_BITS,
+            "Can write at most 64 bits at once, not {}",
+            n
+        );
+        assert!(
+            value & !mask(n) == 0,
+            "Value {} does not fit into {} bits",
+            value,
+            n
+        );
+        if n == 0 {
+            return;
+        }
+        let offset = self.len % WORD_BITS;
+        if offset == 0 {
+            self.words.push(0);
+        }
+        let free = WORD_BITS - offset;
+        let last = self.words.last_mut().unwrap();
+        if n <= free {
+            *last |= value << (free - n);
+        } else {
+            let spill = n - free;
+            *last |= value >> spill;
+            self.words.push(value << (WORD_BITS - spill));
+        }
+        self.len += n;
+    }
+
+    pub fn push(&mut self, bit: bool) {
+        self.write(bit as u64, 1);
+    }
+
+    pub fn extend(&mut self, bits: impl IntoIterator<Item = bool>) {
+        bits.into_iter().for_each(|bit| self.push(bit));
+    }
+
+    fn bit(&self, index: usize) -> bool {
+        self.words[index / WORD_BITS] >> (WORD_BITS - 1 - index % WORD_BITS) & 1 == 1
+    }
+
+    // One '0' or '1' per bit
+    pub fn to_binary(&self) -> String {
+        (0..self.len)
+            .map(|i| if self.bit(i) { '1' } else { '0' })
+            .collect()
+    }
+
+    // Upper case hex digits, the last digit is padded with zero bits.
+    pub fn to_hex(&self) -> String {
+        (0..self.len)
+            .step_by(4)
+            .map(|start| {
+                let digit = (start..start + 4).fold(0, |digit, i| {
+                    digit << 1 | (i < self.len && self.bit(i)) as u32
+                });
+                std::char::from_digit(digit, 16)
+                    .unwrap()
+                    .to_ascii_uppercase()
+            })
+            .collect()
+    }
+
+    pub fn into_reader(self) -> BitReader {
+        BitReader {
+            words: self.words,
+            len: self.len,
+            position: 0,
+        }
+    }
+}
+
+// Reads a packed bit stream from the front, every read fails instead of
+// returning fewer bits than requested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitReader {
+    words: Vec<u64>,
+    len: usize,
+    position: usize,
+}
+
+impl BitReader {
+    pub fn from_bools(bits: impl IntoIterator<Item = bool>) -> BitReader {
+        let mut writer = BitWriter::new();
+        writer.extend(bits);
+        writer.into_reader()
+    }
+
+    // Four bits per hex digit, whitespace is skipped.
+    pub fn from_hex(hex: &str) -> Result<BitReader, BitError> {
+        BitReader::from_digits(hex, 16, 4)
+    }
+
+    // One bit per '0' or '1', whitespace is skipped.
+    pub fn from_binary(binary: &str) -> Result<BitReader, BitError> {
+        BitReader::from_digits(binary, 2, 1)
+    }
+
+    fn from_digits(digits: &str, radix: u32, bits: usize) -> Result<BitReader, BitError> {
+        let mut writer = BitWriter::new();
+        for (position, digit) in digits.chars().enumerate() {
+            if digit.is_whitespace() {
+                continue;
+            }
+            let value = digit
+                .to_digit(radix)
+                .ok_or(BitError::InvalidDigit { position, digit })?;
+            writer.write(value as u64, bits);
+        }
+        Ok(writer.into_reader())
+    }
+
+    // Total number of bits, read or not
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Number of bits read so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.len - self.position
+    }
+
+    // Next `n` bits as a number, the first bit being the most significant.
+    pub fn read(&mut self, n: usize) -> Result<u64, BitError> {
+        let value = self.peek(n)?;
+        self.position += n;
+        Ok(value)
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, BitError> {
+        Ok(self.read(1)? == 1)
+    }
+
+    // Like read, but without moving forward
+    pub fn peek(&self, n: usize) -> Result<u64, BitError> {
+        if n > WORD_BITS {
+            return Err(BitError::TooWide(n));
+        }
+        if n > self.remaining() {
+            return Err(BitError::OutOfBits {
+                requested: n,
+                remaining: self.remaining(),
+            });
+        }
+        if n == 0 {
+            return Ok(0);
+        }
+        let word = self.position / WORD_BITS;
+        let available = WORD_BITS - self.position % WORD_BITS;
+        let high = self.words[word] & mask(available);
+        if n <= available {
+            Ok(high >> (available - n))
+        } else {
+            let spill = n - available;
+            Ok(high << spill | self.words[word + 1] >> (WORD_BITS - spill))
+        }
+    }
+
+    pub fn skip(&mut self, n: usize) -> Result<(), BitError> {
+        if n > self.remaining() {
+            return Err(BitError::OutOfBits {
+                requested: n,
+                remaining: self.remaining(),
+            });
+        }
+        self.position += n;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn hex_and_binary() {
+        let mut reader = BitReader::from_hex("D2FE28").unwrap();
+        assert_eq!(
+            reader,
+            BitReader::from_binary("110100101111111000101000").unwrap()
+        );
+        assert_eq!(reader.len(), 24);
+        assert_eq!(reader.read(3), Ok(6));
+        assert_eq!(reader.read(3), Ok(4));
+        assert_eq!(reader.read_bool(), Ok(true));
+        assert_eq!(reader.position(), 7);
+        assert_eq!(reader.remaining(), 17);
+        assert_eq!(
+            BitReader::from_hex("12 x4"),
+            Err(BitError::InvalidDigit {
+                position: 3,
+                digit: 'x'
+            })
+        );
+        assert!(BitReader::from_binary("0102").is_err());
+    }
+
+    #[test]
+    fn reads_across_words() {
+        let mut writer = BitWriter::new();
+        writer.write(0b101, 3);
+        writer.write(u64::MAX - 1, 64);
+        writer.write(0x1234_5678_9abc, 48);
+        writer.push(true);
+        assert_eq!(writer.len(), 116);
+
+        let mut reader = writer.clone().into_reader();
+        assert_eq!(reader.peek(3), Ok(0b101));
+        assert_eq!(reader.read(3), Ok(0b101));
+        assert_eq!(reader.read(64), Ok(u64::MAX - 1));
+        assert_eq!(reader.read(48), Ok(0x1234_5678_9abc));
+        assert_eq!(reader.read_bool(), Ok(true));
+        assert_eq!(reader.remaining(), 0);
+
+        let mut round_trip = BitReader::from_hex(&writer.to_hex()).unwrap();
+        assert_eq!(round_trip.len(), 116);
+        assert_eq!(round_trip.read(3), Ok(0b101));
+        let binary = writer.to_binary();
+        assert_eq!(binary.len(), 116);
+        assert_eq!(BitReader::from_binary(&binary), Ok(writer.into_reader()));
+    }
+
+    #[test]
+    fn running_out_of_bits() {
+        let mut reader = BitReader::from_binary("10110").unwrap();
+        assert_eq!(reader.read(4), Ok(0b1011));
+        assert_eq!(
+            reader.read(2),
+            Err(BitError::OutOfBits {
+                requested: 2,
+                remaining: 1
+            })
+        );
+        // A failed read does not consume anything
+        assert_eq!(reader.position(), 4);
+        assert_eq!(reader.read(65), Err(BitError::TooWide(65)));
+        assert!(reader.skip(2).is_err());
+        assert_eq!(reader.skip(1), Ok(()));
+        assert_eq!(reader.read(0), Ok(0));
+        assert_eq!(
+            reader.read_bool().unwrap_err().to_string(),
+            "Requested 1 bits but only 0 are left"
+        );
+    }
+
+    #[test]
+    fn hex_padding() {
+        let mut writer = BitWriter::new();
+        writer.extend([true, false, true, true, true]);
+        assert_eq!(writer.to_binary(), "10111");
+        assert_eq!(writer.to_hex(), "B8");
+        assert_eq!(BitWriter::new().to_hex(), "");
+    }
+
+    #[test]
+    #[should_panic(expected = "Value 8 does not fit into 3 bits")]
+    fn write_checks_width() {
+        BitWriter::new().write(8, 3);
+    }
+}
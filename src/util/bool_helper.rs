@@ -1,8 +1,24 @@
-pub fn vec_to_number(binary_number: &[bool]) -> i32 {
-    binary_number
-        .iter()
-        .rev()
-        .enumerate()
-        .map(|(pos, digit)| 2_i32.pow(pos as u32) * *digit as i32)
-        .sum()
+use crate::util::bits::BitReader;
+
+// Reads the flags as a binary number, most significant bit first. Panics for
+// more than 64 flags.
+pub fn vec_to_number(binary_number: &[bool]) -> u64 {
+    BitReader::from_bools(binary_number.iter().copied())
+        .read(binary_number.len())
+        .unwrap_or_else(|error| panic!("{}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn wider_than_32_bits() {
+        assert_eq!(vec_to_number(&[]), 0);
+        assert_eq!(vec_to_number(&[true, false, true, true]), 11);
+        let mut flags = vec![true; 40];
+        flags[39] = false;
+        assert_eq!(vec_to_number(&flags), (1 << 40) - 2);
+    }
 }
@@ -0,0 +1,149 @@
+#![allow(unused)]
+use itertools::Itertools;
+
+// Axis-aligned box in N dimensions covering the integer points `min[i] <= p[i] < max[i]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cuboid<const N: usize> {
+    pub min: [i64; N],
+    pub max: [i64; N],
+}
+
+impl<const N: usize> Cuboid<N> {
+    pub fn new(min: [i64; N], max: [i64; N]) -> Cuboid<N> {
+        Cuboid { min, max }
+    }
+
+    // Both corners included
+    pub fn from_inclusive(min: [i64; N], max: [i64; N]) -> Cuboid<N> {
+        Cuboid {
+            min,
+            max: max.map(|end| end + 1),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..N).any(|axis| self.min[axis] >= self.max[axis])
+    }
+
+    pub fn volume(&self) -> u128 {
+        (0..N)
+            .map(|axis| (self.max[axis] - self.min[axis]).max(0) as u128)
+            .product()
+    }
+
+    pub fn contains(&self, point: &[i64; N]) -> bool {
+        (0..N).all(|axis| (self.min[axis]..self.max[axis]).contains(&point[axis]))
+    }
+
+    pub fn intersect(&self, other: &Cuboid<N>) -> Option<Cuboid<N>> {
+        let intersection = Cuboid {
+            min: std::array::from_fn(|axis| self.min[axis].max(other.min[axis])),
+            max: std::array::from_fn(|axis| self.max[axis].min(other.max[axis])),
+        };
+        (!intersection.is_empty()).then_some(intersection)
+    }
+
+    // Disjoint boxes covering exactly the points of `self` that are not in `other`, at most 2N of them.
+    // Slabs are cut off axis by axis, the remaining core shrinks towards the intersection.
+    pub fn subtract(&self, other: &Cuboid<N>) -> Vec<Cuboid<N>> {
+        let Some(intersection) = self.intersect(other) else {
+            return if self.is_empty() { vec![] } else { vec![*self] };
+        };
+        let mut pieces = vec![];
+        let mut core = *self;
+        for axis in 0..N {
+            if core.min[axis] < intersection.min[axis] {
+                let mut below = core;
+                below.max[axis] = intersection.min[axis];
+                pieces.push(below);
+                core.min[axis] = intersection.min[axis];
+            }
+            if intersection.max[axis] < core.max[axis] {
+                let mut above = core;
+                above.min[axis] = intersection.max[axis];
+                pieces.push(above);
+                core.max[axis] = intersection.max[axis];
+            }
+        }
+        pieces
+    }
+}
+
+// Disjoint boxes covering the points left after applying the boxes in order, `true` adds a box
+// and `false` removes it.
+pub fn signed_union<const N: usize>(
+    boxes: impl IntoIterator<Item = (bool, Cuboid<N>)>,
+) -> Vec<Cuboid<N>> {
+    let mut disjoint: Vec<Cuboid<N>> = vec![];
+    for (add, next) in boxes {
+        disjoint = disjoint
+            .iter()
+            .flat_map(|existing| existing.subtract(&next))
+            .collect_vec();
+        if add && !next.is_empty() {
+            disjoint.push(next);
+        }
+    }
+    disjoint
+}
+
+// Volume covered after applying the boxes in order. Works on disjoint boxes, so nothing is
+// counted twice.
+pub fn signed_union_volume<const N: usize>(
+    boxes: impl IntoIterator<Item = (bool, Cuboid<N>)>,
+) -> u128 {
+    signed_union(boxes).iter().map(Cuboid::volume).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn volume_and_intersection() {
+        let a = Cuboid::from_inclusive([0, 0, 0], [2, 2, 2]);
+        let b = Cuboid::new([1, 1, 1], [5, 5, 5]);
+        assert_eq!(a.volume(), 27);
+        assert_eq!(a.intersect(&b), Some(Cuboid::new([1, 1, 1], [3, 3, 3])));
+        assert_eq!(a.intersect(&Cuboid::new([3, 0, 0], [4, 1, 1])), None);
+        assert!(a.contains(&[2, 2, 2]));
+        assert!(!a.contains(&[3, 2, 2]));
+        assert_eq!(Cuboid::new([0, 0], [-1, 5]).volume(), 0);
+    }
+
+    #[test]
+    fn subtract_is_disjoint_and_exact() {
+        let a = Cuboid::new([0, 0, 0], [4, 4, 4]);
+        let b = Cuboid::new([1, 1, 1], [2, 2, 6]);
+        let pieces = a.subtract(&b);
+        assert_eq!(
+            pieces.iter().map(Cuboid::volume).sum::<u128>(),
+            a.volume() - a.intersect(&b).unwrap().volume()
+        );
+        for (i, first) in pieces.iter().enumerate() {
+            for second in &pieces[i + 1..] {
+                assert_eq!(first.intersect(second), None);
+            }
+        }
+        assert!(pieces.iter().all(|piece| piece.intersect(&b).is_none()));
+
+        assert_eq!(a.subtract(&a), vec![]);
+        assert_eq!(a.subtract(&Cuboid::new([9, 9, 9], [10, 10, 10])), vec![a]);
+    }
+
+    #[test]
+    fn signed_union() {
+        let volume = signed_union_volume([
+            (true, Cuboid::from_inclusive([10, 10, 10], [12, 12, 12])),
+            (true, Cuboid::from_inclusive([11, 11, 11], [13, 13, 13])),
+            (false, Cuboid::from_inclusive([9, 9, 9], [11, 11, 11])),
+            (true, Cuboid::from_inclusive([10, 10, 10], [10, 10, 10])),
+        ]);
+        assert_eq!(volume, 39);
+        assert_eq!(
+            signed_union_volume([(true, Cuboid::new([0], [1_000_000_000_000]))]),
+            1_000_000_000_000
+        );
+    }
+}
@@ -0,0 +1,189 @@
+#![allow(unused)]
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+// Disjoint-set forest with path compression and union by size.
+#[derive(Debug, Clone)]
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    sets: usize,
+}
+
+impl DisjointSet {
+    pub fn new(len: usize) -> DisjointSet {
+        DisjointSet {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            sets: len,
+        }
+    }
+
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut current = element;
+        while self.parent[current] != root {
+            current = std::mem::replace(&mut self.parent[current], root);
+        }
+        root
+    }
+
+    // Returns false if both elements already were in the same set
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        let (large, small) = if self.size[a] >= self.size[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parent[small] = large;
+        self.size[large] += self.size[small];
+        self.sets -= 1;
+        true
+    }
+
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    // Number of elements in the set containing `element`
+    pub fn set_size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.size[root]
+    }
+
+    pub fn sets(&self) -> usize {
+        self.sets
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+}
+
+pub type Cell<const N: usize> = [i32; N];
+
+// Connected components of a set of cells, ids are `0..sizes.len()`.
+#[derive(Debug, Clone)]
+pub struct Components<const N: usize> {
+    pub labels: HashMap<Cell<N>, usize>,
+    pub sizes: Vec<usize>,
+    // Inclusive (min, max) corner of every component
+    pub bounds: Vec<(Cell<N>, Cell<N>)>,
+}
+
+impl<const N: usize> Components<N> {
+    pub fn label(&self, cell: &Cell<N>) -> Option<usize> {
+        self.labels.get(cell).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sizes.len()
+    }
+}
+
+// Cells sharing a face are connected, i.e. 4-neighborhood on grids and 6-neighborhood for voxels.
+pub fn face_neighbors<const N: usize>(cell: &Cell<N>) -> impl Iterator<Item = Cell<N>> + '_ {
+    (0..N).flat_map(move |axis| {
+        [-1, 1].into_iter().map(move |offset| {
+            let mut neighbor = *cell;
+            neighbor[axis] += offset;
+            neighbor
+        })
+    })
+}
+
+// Label the face connected components of `cells` in a single pass.
+// Ids are handed out in the order the components are first seen in sorted cell order.
+pub fn label_components<const N: usize>(cells: impl IntoIterator<Item = Cell<N>>) -> Components<N> {
+    let cells = cells.into_iter().sorted().dedup().collect_vec();
+    let index: HashMap<Cell<N>, usize> = cells
+        .iter()
+        .enumerate()
+        .map(|(i, cell)| (*cell, i))
+        .collect();
+
+    let mut sets = DisjointSet::new(cells.len());
+    for (i, cell) in cells.iter().enumerate() {
+        for neighbor in face_neighbors(cell) {
+            if let Some(j) = index.get(&neighbor) {
+                sets.union(i, *j);
+            }
+        }
+    }
+
+    let mut ids = HashMap::<usize, usize>::new();
+    let mut labels = HashMap::new();
+    let mut sizes = vec![];
+    let mut bounds: Vec<(Cell<N>, Cell<N>)> = vec![];
+    for (i, cell) in cells.into_iter().enumerate() {
+        let root = sets.find(i);
+        let next_id = ids.len();
+        let id = *ids.entry(root).or_insert(next_id);
+        if id == sizes.len() {
+            sizes.push(0);
+            bounds.push((cell, cell));
+        }
+        sizes[id] += 1;
+        let (min, max) = &mut bounds[id];
+        for axis in 0..N {
+            min[axis] = min[axis].min(cell[axis]);
+            max[axis] = max[axis].max(cell[axis]);
+        }
+        labels.insert(cell, id);
+    }
+
+    Components {
+        labels,
+        sizes,
+        bounds,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn disjoint_set() {
+        let mut sets = DisjointSet::new(5);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 4));
+        assert!(!sets.union(1, 0));
+        assert!(sets.same_set(0, 1));
+        assert!(!sets.same_set(1, 3));
+        assert_eq!(sets.set_size(4), 2);
+        assert_eq!(sets.sets(), 3);
+        assert!(sets.union(1, 4));
+        assert_eq!(sets.set_size(0), 4);
+        assert_eq!(sets.sets(), 2);
+    }
+
+    #[test]
+    fn grid_components() {
+        // ##.
+        // ..#
+        // .##
+        let components = label_components([[0, 0], [1, 0], [2, 1], [1, 2], [2, 2]]);
+        assert_eq!(components.len(), 2);
+        assert_eq!(components.sizes, vec![2, 3]);
+        assert_eq!(components.label(&[2, 1]), Some(1));
+        assert_eq!(components.label(&[0, 1]), None);
+        assert_eq!(components.bounds, vec![([0, 0], [1, 0]), ([1, 1], [2, 2])]);
+    }
+
+    #[test]
+    fn diagonal_voxels_are_separate() {
+        let components = label_components([[0, 0, 0], [1, 1, 1], [1, 1, 2]]);
+        assert_eq!(components.sizes, vec![1, 2]);
+        assert_eq!(components.bounds[1], ([1, 1, 1], [1, 1, 2]));
+    }
+}
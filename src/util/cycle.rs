@@ -0,0 +1,199 @@
+#![allow(unused)]
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// A simulation that repeats itself after `start` steps, every `period` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    // The step in the first repetition that behaves like step `n`
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+}
+
+// Detect a repetition by remembering the step every key was first seen at.
+// `key` has to capture everything that determines the following steps.
+// Gives up after `max_steps`.
+pub fn detect_cycle<State, Key>(
+    mut state: State,
+    mut step: impl FnMut(&mut State),
+    key: impl Fn(&State) -> Key,
+    max_steps: usize,
+) -> Option<Cycle>
+where
+    Key: Eq + Hash,
+{
+    let mut seen = HashMap::new();
+    for steps in 0..=max_steps {
+        if let Some(start) = seen.insert(key(&state), steps) {
+            return Some(Cycle {
+                start,
+                period: steps - start,
+            });
+        }
+        step(&mut state);
+    }
+    None
+}
+
+// Brent's algorithm: same result as `detect_cycle` but only ever keeps two states and two keys
+// around, at the price of simulating roughly three times as many steps.
+pub fn detect_cycle_brent<State, Key>(
+    initial: &State,
+    mut step: impl FnMut(&mut State),
+    key: impl Fn(&State) -> Key,
+    max_steps: usize,
+) -> Option<Cycle>
+where
+    State: Clone,
+    Key: Eq,
+{
+    // Find the period: the tortoise waits at powers of two for the hare to come around
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    step(&mut hare);
+    let mut steps = 1;
+    while key(&tortoise) != key(&hare) {
+        if steps > max_steps {
+            return None;
+        }
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        step(&mut hare);
+        period += 1;
+        steps += 1;
+    }
+
+    // Find the start: walk two states `period` steps apart until they meet
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..period {
+        step(&mut hare);
+    }
+    let mut start = 0;
+    while key(&tortoise) != key(&hare) {
+        step(&mut tortoise);
+        step(&mut hare);
+        start += 1;
+    }
+    Some(Cycle { start, period })
+}
+
+// Value of `track` after `n` steps. Only the steps up to the end of the first repetition are simulated,
+// every further repetition is assumed to change the tracked value by the same amount.
+pub fn extrapolate<State>(
+    mut state: State,
+    mut step: impl FnMut(&mut State),
+    track: impl Fn(&State) -> i64,
+    cycle: Cycle,
+    n: usize,
+) -> i64 {
+    let end = cycle.start + cycle.period;
+    let mut values = vec![track(&state)];
+    for _ in 0..n.min(end) {
+        step(&mut state);
+        values.push(track(&state));
+    }
+    if n <= end {
+        return values[n];
+    }
+    let repetitions = ((n - cycle.start) / cycle.period) as i64;
+    let per_period = values[end] - values[cycle.start];
+    values[cycle.reduce(n)] + repetitions * per_period
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    // 3 -> 10 -> 5 -> 16 -> 8 -> 4 -> 2 -> 1 -> 4 -> ...
+    fn collatz(n: &mut u64) {
+        *n = match *n % 2 {
+            0 => *n / 2,
+            _ => 3 * *n + 1,
+        };
+    }
+
+    #[test]
+    fn hashed() {
+        let cycle = detect_cycle(3_u64, collatz, |n| *n, 100);
+        assert_eq!(
+            cycle,
+            Some(Cycle {
+                start: 5,
+                period: 3
+            })
+        );
+        assert_eq!(detect_cycle(3_u64, collatz, |n| *n, 7), None);
+    }
+
+    #[test]
+    fn brent() {
+        assert_eq!(
+            detect_cycle_brent(&3_u64, collatz, |n| *n, 100),
+            Some(Cycle {
+                start: 5,
+                period: 3
+            })
+        );
+        assert_eq!(
+            detect_cycle_brent(&4_u64, collatz, |n| *n, 100),
+            Some(Cycle {
+                start: 0,
+                period: 3
+            })
+        );
+        assert_eq!(detect_cycle_brent(&3_u64, collatz, |n| *n, 4), None);
+    }
+
+    #[test]
+    fn extrapolate_growing_quantity() {
+        // Steps through 0, 1, 2, 0, 1, 2, ... and counts how often it has been at 0
+        let step = |(position, zeros): &mut (u8, i64)| {
+            *position = (*position + 1) % 3;
+            if *position == 0 {
+                *zeros += 1;
+            }
+        };
+        let cycle = detect_cycle((0, 0), step, |state| state.0, 10).unwrap();
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 0,
+                period: 3
+            }
+        );
+        for n in [0, 2, 3, 7, 1_000_000_000_000] {
+            assert_eq!(
+                extrapolate((0, 0), step, |state| state.1, cycle, n),
+                n as i64 / 3
+            );
+        }
+    }
+
+    #[test]
+    fn reduce() {
+        let cycle = Cycle {
+            start: 5,
+            period: 3,
+        };
+        assert_eq!(cycle.reduce(4), 4);
+        assert_eq!(cycle.reduce(5), 5);
+        assert_eq!(cycle.reduce(9), 6);
+    }
+}
@@ -1,38 +1,1067 @@
 #![allow(unused)]
-pub fn grid_to_string(points: &[(i64, i64)]) -> String {
-    let _min = {
-        (
-            *points.iter().map(|(x, _)| x).min().unwrap(),
-            *points.iter().map(|(_, y)| y).min().unwrap(),
-        )
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Stdout, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+use crate::util::boxes::Cuboid;
+
+type Point = (i64, i64);
+
+// Lines are drawn from the highest y down for YUp, like a plot, and from the
+// lowest y down for YDown, like a picture or puzzle input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    YUp,
+    YDown,
+}
+
+// Inclusive rectangle of cells to draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Viewport {
+    pub fn new(min: Point, max: Point) -> Viewport {
+        Viewport { min, max }
+    }
+
+    // Smallest viewport containing all points, None for no points
+    pub fn bounding(points: impl IntoIterator<Item = Point>) -> Option<Viewport> {
+        points.into_iter().fold(None, |viewport, point| {
+            Some(match viewport {
+                None => Viewport::new(point, point),
+                Some(Viewport { min, max }) => Viewport::new(
+                    (min.0.min(point.0), min.1.min(point.1)),
+                    (max.0.max(point.0), max.1.max(point.1)),
+                ),
+            })
+        })
+    }
+}
+
+// Renders sparse grids as text. By default the view is cropped to the bounding
+// box of the cells and markers, y points up and empty cells are '.'.
+#[derive(Debug, Clone)]
+pub struct GridRenderer {
+    viewport: Option<Viewport>,
+    orientation: Orientation,
+    empty: char,
+    markers: Vec<(Point, char)>,
+}
+
+impl Default for GridRenderer {
+    fn default() -> Self {
+        GridRenderer::new()
+    }
+}
+
+impl GridRenderer {
+    pub fn new() -> GridRenderer {
+        GridRenderer {
+            viewport: None,
+            orientation: Orientation::YUp,
+            empty: '.',
+            markers: vec![],
+        }
+    }
+
+    // Only draw the given rectangle, cells outside of it are left out
+    pub fn viewport(mut self, viewport: Viewport) -> GridRenderer {
+        self.viewport = Some(viewport);
+        self
+    }
+
+    pub fn orientation(mut self, orientation: Orientation) -> GridRenderer {
+        self.orientation = orientation;
+        self
+    }
+
+    pub fn empty(mut self, empty: char) -> GridRenderer {
+        self.empty = empty;
+        self
+    }
+
+    // Draws `label` on top of the given points, e.g. 'S' for the start or '@'
+    // for a falling rock. Markers added later win over earlier ones.
+    pub fn marker(mut self, label: char, points: impl IntoIterator<Item = Point>) -> GridRenderer {
+        self.markers
+            .extend(points.into_iter().map(|point| (point, label)));
+        self
+    }
+
+    // Draws every cell with the character `glyph` picks for its state
+    pub fn render<State>(
+        &self,
+        cells: &HashMap<Point, State>,
+        glyph: impl Fn(&State) -> char,
+    ) -> String {
+        let viewport = match self.resolve_viewport(cells.keys()) {
+            Some(viewport) => viewport,
+            None => return String::new(),
+        };
+        let markers: HashMap<Point, char> = self.markers.iter().copied().collect();
+        self.rows(&viewport)
+            .into_iter()
+            .flat_map(|y| {
+                (viewport.min.0..=viewport.max.0)
+                    .map(move |x| (x, y))
+                    .map(|point| {
+                        markers
+                            .get(&point)
+                            .copied()
+                            .unwrap_or_else(|| cells.get(&point).map(&glyph).unwrap_or(self.empty))
+                    })
+                    .chain(['\n'])
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    // Draws the given points as '#'
+    pub fn render_points(&self, points: &[Point]) -> String {
+        let cells = points.iter().map(|&point| (point, ())).collect();
+        self.render(&cells, |_| '#')
+    }
+
+    // Same layout as `render`, but every cell becomes a square of
+    // `style.scale` pixels colored by `palette`.
+    pub fn render_image<State>(
+        &self,
+        cells: &HashMap<Point, State>,
+        palette: impl Fn(&State) -> Color,
+        style: &ImageStyle,
+    ) -> Image {
+        let viewport = match self.resolve_viewport(cells.keys()) {
+            Some(viewport) => viewport,
+            None => return Image::new(0, 0, style.background),
+        };
+        let markers: HashMap<Point, char> = self.markers.iter().copied().collect();
+        let columns = (viewport.max.0 - viewport.min.0 + 1) as usize;
+        let rows = self.rows(&viewport);
+        let mut image = Image::new(
+            columns * style.scale,
+            rows.len() * style.scale,
+            style.background,
+        );
+        for (row, y) in rows.into_iter().enumerate() {
+            for (column, x) in (viewport.min.0..=viewport.max.0).enumerate() {
+                let color = match markers.get(&(x, y)) {
+                    Some(label) => style.marker_color(*label),
+                    None => match cells.get(&(x, y)) {
+                        Some(state) => palette(state),
+                        None => continue,
+                    },
+                };
+                image.fill(column * style.scale, row * style.scale, style.scale, color);
+            }
+        }
+        image
+    }
+
+    fn resolve_viewport<'a>(&self, cells: impl Iterator<Item = &'a Point>) -> Option<Viewport> {
+        self.viewport.or_else(|| {
+            Viewport::bounding(
+                cells
+                    .copied()
+                    .chain(self.markers.iter().map(|(point, _)| *point)),
+            )
+        })
+    }
+
+    // y values of the lines in drawing order
+    fn rows(&self, viewport: &Viewport) -> Vec<i64> {
+        match self.orientation {
+            Orientation::YUp => (viewport.min.1..=viewport.max.1).rev().collect(),
+            Orientation::YDown => (viewport.min.1..=viewport.max.1).collect(),
+        }
+    }
+}
+
+pub type Color = [u8; 3];
+
+pub const BLACK: Color = [0, 0, 0];
+pub const WHITE: Color = [255, 255, 255];
+pub const RED: Color = [255, 0, 0];
+
+// Linear blend from dark blue at `min` to yellow at `max`, e.g. for heightmaps
+pub fn gradient(value: i64, min: i64, max: i64) -> Color {
+    let t = if max > min {
+        (value.clamp(min, max) - min) as f64 / (max - min) as f64
+    } else {
+        0.0
     };
-    let max = {
-        (
-            *points.iter().map(|(x, _)| x).max().unwrap(),
-            *points.iter().map(|(_, y)| y).max().unwrap(),
+    let blend = |from: f64, to: f64| (from + (to - from) * t).round() as u8;
+    [blend(20.0, 250.0), blend(30.0, 220.0), blend(120.0, 40.0)]
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageStyle {
+    pub background: Color,
+    // Width and height of a single cell in pixels
+    pub scale: usize,
+    // Markers without a color of their own are drawn red
+    pub marker_colors: HashMap<char, Color>,
+}
+
+impl Default for ImageStyle {
+    fn default() -> Self {
+        ImageStyle {
+            background: BLACK,
+            scale: 1,
+            marker_colors: HashMap::new(),
+        }
+    }
+}
+
+impl ImageStyle {
+    fn marker_color(&self, label: char) -> Color {
+        self.marker_colors.get(&label).copied().unwrap_or(RED)
+    }
+}
+
+// RGB image, pixels stored row by row from the top left
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Color) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Color) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    // Colors the size x size square with its top left corner at (x, y)
+    pub fn fill(&mut self, x: usize, y: usize, size: usize, color: Color) {
+        for dy in 0..size {
+            for dx in 0..size {
+                self.set(x + dx, y + dy, color);
+            }
+        }
+    }
+
+    // Binary PPM (P6), readable by most image viewers
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        bytes.extend(self.pixels.iter().flatten());
+        bytes
+    }
+
+    // PNG with uncompressed deflate blocks, so no compression library is needed.
+    // The files are about as big as the PPM ones.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut header = vec![];
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // 8 bit depth, RGB, default compression, filter and no interlacing
+        header.extend([8, 2, 0, 0, 0]);
+
+        // Every line starts with filter type 0, meaning no filter
+        let mut raw = Vec::with_capacity(self.height * (1 + 3 * self.width));
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+
+        let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        png_chunk(&mut png, b"IHDR", &header);
+        png_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    // Writes PNG for a .png extension and PPM otherwise
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let bytes = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("png") => self.to_png(),
+            _ => self.to_ppm(),
+        };
+        fs::write(path, bytes)
+    }
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffff_u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+// zlib stream made of stored deflate blocks of at most 65535 bytes
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut stream = vec![0x78, 0x01];
+    let blocks = data.chunks(65535).collect::<Vec<_>>();
+    if blocks.is_empty() {
+        stream.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    for (i, block) in blocks.iter().enumerate() {
+        stream.push(u8::from(i + 1 == blocks.len()));
+        let len = block.len() as u16;
+        stream.extend(len.to_le_bytes());
+        stream.extend((!len).to_le_bytes());
+        stream.extend(*block);
+    }
+    stream.extend(adler32(data).to_be_bytes());
+    stream
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+}
+
+// Writes images as numbered files, e.g. frames/sand_00000.ppm, frames/sand_00001.ppm
+#[derive(Debug, Clone)]
+pub struct Frames {
+    directory: PathBuf,
+    prefix: String,
+    format: ImageFormat,
+    next: usize,
+}
+
+impl Frames {
+    pub fn new(directory: impl Into<PathBuf>, prefix: &str, format: ImageFormat) -> Frames {
+        Frames {
+            directory: directory.into(),
+            prefix: prefix.to_string(),
+            format,
+            next: 0,
+        }
+    }
+
+    // Writes the next frame and returns its path, creating the directory if needed
+    pub fn write(&mut self, image: &Image) -> io::Result<PathBuf> {
+        fs::create_dir_all(&self.directory)?;
+        let extension = match self.format {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+        };
+        let path = self
+            .directory
+            .join(format!("{}_{:05}.{}", self.prefix, self.next, extension));
+        image.save(&path)?;
+        self.next += 1;
+        Ok(path)
+    }
+
+    // Number of frames written so far
+    pub fn len(&self) -> usize {
+        self.next
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shape {
+    // Dots centered on the points
+    Points(Vec<Point>),
+    // Open line through the points
+    Polyline(Vec<Point>),
+    // Closed outline through the points
+    Polygon(Vec<Point>),
+}
+
+// Named group of shapes sharing a color. The label ends up as the group title, so
+// viewers show it on hover, and reduced to letters, digits and dashes as its id.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    label: String,
+    color: Color,
+    stroke_width: f64,
+    shapes: Vec<Shape>,
+}
+
+impl Layer {
+    pub fn new(label: &str, color: Color) -> Layer {
+        Layer {
+            label: label.to_string(),
+            color,
+            stroke_width: 0.2,
+            shapes: vec![],
+        }
+    }
+
+    // Line width and dot diameter in coordinate units, 0.2 by default
+    pub fn stroke_width(mut self, stroke_width: f64) -> Layer {
+        self.stroke_width = stroke_width;
+        self
+    }
+
+    pub fn points(mut self, points: impl IntoIterator<Item = Point>) -> Layer {
+        self.shapes
+            .push(Shape::Points(points.into_iter().collect()));
+        self
+    }
+
+    pub fn polyline(mut self, points: impl IntoIterator<Item = Point>) -> Layer {
+        self.shapes
+            .push(Shape::Polyline(points.into_iter().collect()));
+        self
+    }
+
+    pub fn polygon(mut self, points: impl IntoIterator<Item = Point>) -> Layer {
+        self.shapes
+            .push(Shape::Polygon(points.into_iter().collect()));
+        self
+    }
+
+    fn all_points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.shapes.iter().flat_map(|shape| match shape {
+            Shape::Points(points) | Shape::Polyline(points) | Shape::Polygon(points) => {
+                points.iter().copied()
+            }
+        })
+    }
+}
+
+// Vector drawing of layers, later layers are drawn on top. Coordinates are kept
+// exact and the view box fits all layers. Unlike the text grids y points down by
+// default, as SVG and most puzzle inputs do.
+#[derive(Debug, Clone)]
+pub struct Svg {
+    orientation: Orientation,
+    layers: Vec<Layer>,
+}
+
+impl Default for Svg {
+    fn default() -> Self {
+        Svg::new()
+    }
+}
+
+impl Svg {
+    pub fn new() -> Svg {
+        Svg {
+            orientation: Orientation::YDown,
+            layers: vec![],
+        }
+    }
+
+    pub fn orientation(mut self, orientation: Orientation) -> Svg {
+        self.orientation = orientation;
+        self
+    }
+
+    pub fn layer(mut self, layer: Layer) -> Svg {
+        self.layers.push(layer);
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let flip = |(x, y): Point| match self.orientation {
+            Orientation::YDown => (x, y),
+            Orientation::YUp => (x, -y),
+        };
+        let margin = self
+            .layers
+            .iter()
+            .map(|layer| layer.stroke_width)
+            .fold(1.0, f64::max);
+        let (min, max) = Viewport::bounding(
+            self.layers
+                .iter()
+                .flat_map(|layer| layer.all_points())
+                .map(flip),
         )
-    };
+        .map_or(((0, 0), (0, 0)), |viewport| (viewport.min, viewport.max));
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+            min.0 as f64 - margin,
+            min.1 as f64 - margin,
+            (max.0 - min.0) as f64 + 2.0 * margin,
+            (max.1 - min.1) as f64 + 2.0 * margin
+        );
+        for layer in &self.layers {
+            let color = format!(
+                "#{:02x}{:02x}{:02x}",
+                layer.color[0], layer.color[1], layer.color[2]
+            );
+            svg += &format!(
+                "<g id=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" stroke-linejoin=\"round\">\n<title>{}</title>\n",
+                svg_id(&layer.label),
+                color,
+                layer.stroke_width,
+                escape_xml(&layer.label)
+            );
+            for shape in &layer.shapes {
+                let coordinates = |points: &[Point]| {
+                    points
+                        .iter()
+                        .map(|&point| {
+                            let (x, y) = flip(point);
+                            format!("{},{}", x, y)
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                };
+                match shape {
+                    Shape::Points(points) => {
+                        for &point in points {
+                            let (x, y) = flip(point);
+                            svg += &format!(
+                                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" stroke=\"none\"/>\n",
+                                x,
+                                y,
+                                layer.stroke_width / 2.0,
+                                color
+                            );
+                        }
+                    }
+                    Shape::Polyline(points) => {
+                        svg += &format!("<polyline points=\"{}\"/>\n", coordinates(points));
+                    }
+                    Shape::Polygon(points) => {
+                        svg += &format!("<polygon points=\"{}\"/>\n", coordinates(points));
+                    }
+                }
+            }
+            svg += "</g>\n";
+        }
+        svg += "</svg>\n";
+        svg
+    }
 
-    //let hash_points: HashSet<(i64, i64)> = points
-    //    .iter()
-    //    .map(|point| (point.0 - min.0, point.1 - min.1))
-    //    .collect();
-    let ref_points = &points;
-    (0..=max.1)
-        .rev()
-        .flat_map(move |y| {
-            (0..=max.0)
-                .map(move |x| {
-                    let point = (x, y);
-                    if ref_points.contains(&point) {
-                        '#'
-                    } else {
-                        '.'
-                    }
-                })
-                .chain(['\n'].into_iter())
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.render())
+    }
+}
+
+// Runs of anything but ASCII letters and digits become a single dash, ids may not
+// contain spaces and some viewers choke on other punctuation
+fn svg_id(label: &str) -> String {
+    let id = label
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if id.is_empty() {
+        "layer".to_string()
+    } else {
+        id
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Commands for a running terminal animation, typed as a line followed by Enter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    // Empty line or "p"
+    TogglePause,
+    // "s" or "n", shows the next frame and stays paused
+    Step,
+    // "q"
+    Quit,
+}
+
+impl Control {
+    pub fn parse(line: &str) -> Option<Control> {
+        match line.trim() {
+            "" | "p" => Some(Control::TogglePause),
+            "s" | "n" => Some(Control::Step),
+            "q" => Some(Control::Quit),
+            _ => None,
+        }
+    }
+}
+
+const CLEAR_SCREEN: &str = "\x1b[2J";
+const CURSOR_HOME: &str = "\x1b[H";
+const CLEAR_BELOW: &str = "\x1b[J";
+
+// Plays frames produced by a closure. On a terminal every frame is redrawn in
+// place with ANSI escapes at a fixed rate and can be paused and stepped
+// through. Headless animators write all frames one after another without
+// waiting, which is what tests and later inspection want.
+pub struct Animator<W: Write> {
+    out: W,
+    // None for headless output
+    delay: Option<Duration>,
+    paused: bool,
+    controls: Option<Receiver<Control>>,
+    max_frames: Option<usize>,
+}
+
+impl Animator<Stdout> {
+    pub fn terminal(frames_per_second: f64) -> Animator<Stdout> {
+        assert!(frames_per_second > 0.0, "Frame rate has to be positive");
+        let (sender, receiver) = mpsc::channel();
+        // Blocks on stdin for the rest of the program, which is fine for a debugging aid
+        thread::spawn(move || {
+            for line in io::stdin().lines() {
+                let control = match line {
+                    Ok(line) => Control::parse(&line),
+                    Err(_) => break,
+                };
+                if let Some(control) = control {
+                    if sender.send(control).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        Animator {
+            out: io::stdout(),
+            delay: Some(Duration::from_secs_f64(1.0 / frames_per_second)),
+            paused: false,
+            controls: Some(receiver),
+            max_frames: None,
+        }
+    }
+}
+
+impl Animator<File> {
+    pub fn headless_file(path: impl AsRef<Path>) -> io::Result<Animator<File>> {
+        Ok(Animator::headless(File::create(path)?))
+    }
+}
+
+impl<W: Write> Animator<W> {
+    pub fn headless(out: W) -> Animator<W> {
+        Animator {
+            out,
+            delay: None,
+            paused: false,
+            controls: None,
+            max_frames: None,
+        }
+    }
+
+    // Waits for a command after the first frame
+    pub fn paused(mut self) -> Animator<W> {
+        self.paused = true;
+        self
+    }
+
+    pub fn max_frames(mut self, max_frames: usize) -> Animator<W> {
+        self.max_frames = Some(max_frames);
+        self
+    }
+
+    // Shows frames until `next_frame` returns None, the frame limit is hit or
+    // the user quits. Returns the number of frames shown.
+    pub fn run(&mut self, mut next_frame: impl FnMut() -> Option<String>) -> io::Result<usize> {
+        if self.delay.is_some() {
+            write!(self.out, "{}", CLEAR_SCREEN)?;
+        }
+        let mut shown = 0;
+        while self.max_frames.is_none_or(|max_frames| shown < max_frames) {
+            let frame = match next_frame() {
+                Some(frame) => frame,
+                None => break,
+            };
+            shown += 1;
+            match self.delay {
+                Some(_) => write!(
+                    self.out,
+                    "{}{}\nFrame {}{}  [Enter/p] pause  [s] step  [q] quit\n{}",
+                    CURSOR_HOME,
+                    frame.trim_end_matches('\n'),
+                    shown,
+                    if self.paused { " (paused)" } else { "" },
+                    CLEAR_BELOW
+                )?,
+                None => write!(
+                    self.out,
+                    "Frame {}\n{}\n",
+                    shown,
+                    frame.trim_end_matches('\n')
+                )?,
+            }
+            self.out.flush()?;
+            if !self.wait() {
+                break;
+            }
+        }
+        Ok(shown)
+    }
+
+    // Waits between two frames, false if the user wants to quit
+    fn wait(&mut self) -> bool {
+        let (delay, controls) = match (self.delay, &self.controls) {
+            (Some(delay), Some(controls)) => (delay, controls),
+            (Some(delay), None) => {
+                thread::sleep(delay);
+                return true;
+            }
+            _ => return true,
+        };
+        loop {
+            let control = if self.paused {
+                match controls.recv() {
+                    Ok(control) => control,
+                    // Nobody can unpause anymore
+                    Err(_) => return true,
+                }
+            } else {
+                match controls.recv_timeout(delay) {
+                    Ok(control) => control,
+                    Err(_) => return true,
+                }
+            };
+            match control {
+                Control::Quit => return false,
+                Control::Step => {
+                    self.paused = true;
+                    return true;
+                }
+                Control::TogglePause => {
+                    self.paused = !self.paused;
+                    if !self.paused {
+                        return true;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SurfaceColors {
+    // Faces reachable from outside
+    pub exterior: Color,
+    // Faces of enclosed air pockets
+    pub interior: Color,
+}
+
+impl SurfaceColors {
+    pub fn uniform(color: Color) -> SurfaceColors {
+        SurfaceColors {
+            exterior: color,
+            interior: color,
+        }
+    }
+}
+
+// Quad with its corners in counterclockwise order seen from outside
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MeshFace {
+    pub vertices: [usize; 4],
+    pub exterior: bool,
+}
+
+// Surface of a set of voxels or boxes, made of only the faces between filled
+// and empty space. Faces are classified as exterior if the empty side is
+// connected to the outside, and interior if it belongs to an enclosed pocket.
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    vertices: Vec<[i64; 3]>,
+    vertex_index: HashMap<[i64; 3], usize>,
+    faces: Vec<MeshFace>,
+}
+
+impl Mesh {
+    // Unit cubes with their minimum corner at the given points
+    pub fn from_voxels(voxels: impl IntoIterator<Item = [i64; 3]>) -> Mesh {
+        Mesh::from_boxes(
+            &voxels
+                .into_iter()
+                .map(|voxel| Cuboid::new(voxel, voxel.map(|c| c + 1)))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    // Union of the boxes. Coordinates are compressed to the box boundaries, so
+    // huge boxes are cheap, but the work grows with the cube of the number of
+    // distinct boundaries per axis.
+    pub fn from_boxes(boxes: &[Cuboid<3>]) -> Mesh {
+        let boxes = boxes
+            .iter()
+            .filter(|cuboid| !cuboid.is_empty())
+            .collect::<Vec<_>>();
+        let mut mesh = Mesh::default();
+        if boxes.is_empty() {
+            return mesh;
+        }
+        // Boundaries per axis, padded by one so that the outermost cells are empty
+        let axes: [Vec<i64>; 3] = std::array::from_fn(|axis| {
+            let mut values = boxes
+                .iter()
+                .flat_map(|cuboid| [cuboid.min[axis], cuboid.max[axis]])
+                .collect::<Vec<_>>();
+            values.sort_unstable();
+            values.dedup();
+            let (first, last) = (values[0], values[values.len() - 1]);
+            values.insert(0, first - 1);
+            values.push(last + 1);
+            values
+        });
+        // Cell i along an axis spans axes[axis][i]..axes[axis][i + 1]
+        let size: [usize; 3] = std::array::from_fn(|axis| axes[axis].len() - 1);
+        let cell_index = |cell: [usize; 3]| (cell[0] * size[1] + cell[1]) * size[2] + cell[2];
+        let position = |axis: usize, value: i64| axes[axis].binary_search(&value).unwrap();
+
+        let mut filled = vec![false; size.iter().product()];
+        for cuboid in &boxes {
+            let ranges: [std::ops::Range<usize>; 3] = std::array::from_fn(|axis| {
+                position(axis, cuboid.min[axis])..position(axis, cuboid.max[axis])
+            });
+            for x in ranges[0].clone() {
+                for y in ranges[1].clone() {
+                    for z in ranges[2].clone() {
+                        filled[cell_index([x, y, z])] = true;
+                    }
+                }
+            }
+        }
+
+        let neighbor = |cell: [usize; 3], axis: usize, positive: bool| {
+            let mut next = cell;
+            if positive {
+                next[axis] += 1;
+                (next[axis] < size[axis]).then_some(next)
+            } else {
+                next[axis] = next[axis].checked_sub(1)?;
+                Some(next)
+            }
+        };
+
+        // Flood the empty cells starting from the padding corner
+        let mut outside = vec![false; filled.len()];
+        let mut queue = vec![[0, 0, 0]];
+        outside[0] = true;
+        while let Some(cell) = queue.pop() {
+            for axis in 0..3 {
+                for positive in [false, true] {
+                    if let Some(next) = neighbor(cell, axis, positive) {
+                        let index = cell_index(next);
+                        if !filled[index] && !outside[index] {
+                            outside[index] = true;
+                            queue.push(next);
+                        }
+                    }
+                }
+            }
+        }
+
+        for x in 0..size[0] {
+            for y in 0..size[1] {
+                for z in 0..size[2] {
+                    let cell = [x, y, z];
+                    if !filled[cell_index(cell)] {
+                        continue;
+                    }
+                    for axis in 0..3 {
+                        for positive in [false, true] {
+                            // Padding guarantees a neighbor for every filled cell
+                            let next = neighbor(cell, axis, positive).unwrap();
+                            if filled[cell_index(next)] {
+                                continue;
+                            }
+                            let min: [i64; 3] = std::array::from_fn(|a| axes[a][cell[a]]);
+                            let max: [i64; 3] = std::array::from_fn(|a| axes[a][cell[a] + 1]);
+                            mesh.add_face(&min, &max, axis, positive, outside[cell_index(next)]);
+                        }
+                    }
+                }
+            }
+        }
+        mesh
+    }
+
+    // Face of the box min..max orthogonal to `axis`, on its upper side if `positive`
+    fn add_face(
+        &mut self,
+        min: &[i64; 3],
+        max: &[i64; 3],
+        axis: usize,
+        positive: bool,
+        exterior: bool,
+    ) {
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        let plane = if positive { max[axis] } else { min[axis] };
+        let corner = |cu: i64, cv: i64| {
+            let mut point = [0; 3];
+            point[axis] = plane;
+            point[u] = cu;
+            point[v] = cv;
+            point
+        };
+        // Counterclockwise in (u, v) has its normal along +axis
+        let mut corners = [
+            corner(min[u], min[v]),
+            corner(max[u], min[v]),
+            corner(max[u], max[v]),
+            corner(min[u], max[v]),
+        ];
+        if !positive {
+            corners.reverse();
+        }
+        let vertices = corners.map(|point| self.vertex(point));
+        self.faces.push(MeshFace { vertices, exterior });
+    }
+
+    fn vertex(&mut self, point: [i64; 3]) -> usize {
+        let vertices = &mut self.vertices;
+        *self.vertex_index.entry(point).or_insert_with(|| {
+            vertices.push(point);
+            vertices.len() - 1
         })
-        .collect()
+    }
+
+    pub fn vertices(&self) -> &[[i64; 3]] {
+        &self.vertices
+    }
+
+    pub fn faces(&self) -> &[MeshFace] {
+        &self.faces
+    }
+
+    // Total area of all faces, or only of the exterior or interior ones
+    pub fn area(&self, exterior: Option<bool>) -> u128 {
+        self.faces
+            .iter()
+            .filter(|face| exterior.is_none_or(|exterior| face.exterior == exterior))
+            .map(|face| {
+                let [a, b, _, d] = face.vertices.map(|i| self.vertices[i]);
+                let length = |p: [i64; 3], q: [i64; 3]| {
+                    (0..3)
+                        .map(|axis| (p[axis] - q[axis]).unsigned_abs() as u128)
+                        .sum::<u128>()
+                };
+                length(a, b) * length(a, d)
+            })
+            .sum()
+    }
+
+    // Wavefront OBJ with faces grouped into the materials "exterior" and
+    // "interior", which `to_mtl` defines
+    pub fn to_obj(&self, material_library: &str) -> String {
+        let mut obj = format!("mtllib {}\n", material_library);
+        for [x, y, z] in &self.vertices {
+            obj += &format!("v {} {} {}\n", x, y, z);
+        }
+        for (name, exterior) in [("exterior", true), ("interior", false)] {
+            obj += &format!("g {}\nusemtl {}\n", name, name);
+            for face in self.faces.iter().filter(|face| face.exterior == exterior) {
+                let [a, b, c, d] = face.vertices.map(|i| i + 1);
+                obj += &format!("f {} {} {} {}\n", a, b, c, d);
+            }
+        }
+        obj
+    }
+
+    pub fn to_mtl(colors: &SurfaceColors) -> String {
+        let material = |name: &str, color: Color| {
+            let [r, g, b] = color.map(|c| c as f64 / 255.0);
+            format!("newmtl {}\nKd {:.3} {:.3} {:.3}\n", name, r, g, b)
+        };
+        material("exterior", colors.exterior) + &material("interior", colors.interior)
+    }
+
+    // ASCII PLY with a color per face
+    pub fn to_ply(&self, colors: &SurfaceColors) -> String {
+        let mut ply = format!(
+            "ply\nformat ascii 1.0\n\
+             element vertex {}\nproperty int x\nproperty int y\nproperty int z\n\
+             element face {}\nproperty list uchar int vertex_indices\n\
+             property uchar red\nproperty uchar green\nproperty uchar blue\nend_header\n",
+            self.vertices.len(),
+            self.faces.len()
+        );
+        for [x, y, z] in &self.vertices {
+            ply += &format!("{} {} {}\n", x, y, z);
+        }
+        for face in &self.faces {
+            let [a, b, c, d] = face.vertices;
+            let [red, green, blue] = if face.exterior {
+                colors.exterior
+            } else {
+                colors.interior
+            };
+            ply += &format!("4 {} {} {} {} {} {} {}\n", a, b, c, d, red, green, blue);
+        }
+        ply
+    }
+
+    // PLY for a .ply extension, otherwise OBJ with its materials next to it
+    pub fn save(&self, path: impl AsRef<Path>, colors: &SurfaceColors) -> io::Result<()> {
+        let path = path.as_ref();
+        if path.extension().and_then(|extension| extension.to_str()) == Some("ply") {
+            return fs::write(path, self.to_ply(colors));
+        }
+        let material_path = path.with_extension("mtl");
+        let material_library = material_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .expect("Path should have a file name");
+        fs::write(&material_path, Mesh::to_mtl(colors))?;
+        fs::write(path, self.to_obj(material_library))
+    }
+}
+
+// Points as '#' with y pointing up. The view always includes the origin and
+// grows into negative coordinates if needed.
+pub fn grid_to_string(points: &[(i64, i64)]) -> String {
+    let viewport = Viewport::bounding(points.iter().copied().chain([(0, 0)])).unwrap();
+    GridRenderer::new().viewport(viewport).render_points(points)
 }
 
 #[cfg(test)]
@@ -51,4 +1080,292 @@ mod tests {
         assert_eq!(grid_to_string(&[(0, 0), (1, 0)]), "##\n");
         assert_eq!(grid_to_string(&[(0, 0), (0, 1)]), "#\n#\n");
     }
+
+    #[test]
+    fn draw_negative_points() {
+        assert_eq!(grid_to_string(&[(-2, -1)]), "...\n#..\n");
+        assert_eq!(grid_to_string(&[(-2, -1), (0, 0)]), "..#\n#..\n");
+    }
+
+    #[test]
+    fn crop_to_bounding_box() {
+        let renderer = GridRenderer::new();
+        assert_eq!(renderer.render_points(&[(5, 5), (6, 7)]), ".#\n..\n#.\n");
+        assert_eq!(renderer.render_points(&[]), "");
+    }
+
+    #[test]
+    fn viewport_and_orientation() {
+        let points = [(0, 0), (1, 1), (5, 5)];
+        let renderer = GridRenderer::new().viewport(Viewport::new((-1, 0), (1, 1)));
+        assert_eq!(renderer.render_points(&points), "..#\n.#.\n");
+        assert_eq!(
+            renderer
+                .orientation(Orientation::YDown)
+                .render_points(&points),
+            ".#.\n..#\n"
+        );
+    }
+
+    #[test]
+    fn glyphs_and_markers() {
+        let cells = HashMap::from([((0, 0), 1), ((1, 0), 2), ((2, 0), 3)]);
+        let renderer = GridRenderer::new()
+            .empty(' ')
+            .marker('S', [(0, 0)])
+            .marker('E', [(3, 1)]);
+        assert_eq!(
+            renderer.render(&cells, |height| char::from_digit(*height, 10).unwrap()),
+            "   E\nS23 \n"
+        );
+    }
+
+    #[test]
+    fn image_with_palette_and_scale() {
+        let cells = HashMap::from([((0, 0), 0), ((1, 0), 9), ((1, 1), 5)]);
+        let style = ImageStyle {
+            scale: 2,
+            marker_colors: HashMap::from([('S', WHITE)]),
+            ..ImageStyle::default()
+        };
+        let image = GridRenderer::new().marker('S', [(0, 0)]).render_image(
+            &cells,
+            |height| gradient(*height, 0, 9),
+            &style,
+        );
+        assert_eq!((image.width(), image.height()), (4, 4));
+        // y points up, so (1, 1) is the top right cell
+        assert_eq!(image.get(3, 1), gradient(5, 0, 9));
+        assert_eq!(image.get(0, 0), BLACK);
+        assert_eq!(image.get(1, 3), WHITE);
+        assert_eq!(image.get(2, 2), gradient(9, 0, 9));
+        assert_eq!(gradient(9, 0, 9), [250, 220, 40]);
+    }
+
+    #[test]
+    fn ppm() {
+        let mut image = Image::new(2, 1, BLACK);
+        image.set(1, 0, [1, 2, 3]);
+        assert_eq!(image.to_ppm(), b"P6\n2 1\n255\n\0\0\0\x01\x02\x03".to_vec());
+    }
+
+    #[test]
+    fn png_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn png() {
+        let image = Image::new(3, 2, RED);
+        let png = image.to_png();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], [0, 0, 0, 3, 0, 0, 0, 2]);
+        assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xaeB`\x82");
+        // 2 lines of a filter byte and 3 pixels, stored in a single block
+        let idat_length = u32::from_be_bytes(png[33..37].try_into().unwrap());
+        assert_eq!(idat_length, 2 + 5 + 2 * 10 + 4);
+    }
+
+    #[test]
+    fn svg_layers() {
+        let svg = Svg::new()
+            .layer(Layer::new("rock", [120, 120, 120]).polyline([(0, 0), (4, 0), (4, 2)]))
+            .layer(
+                Layer::new("sensor <1>", RED)
+                    .stroke_width(0.5)
+                    .polygon([(2, -1), (3, 0), (2, 1), (1, 0)])
+                    .points([(2, 0)]),
+            )
+            .render();
+        assert_eq!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"-1 -2 6 5\">\n\
+             <g id=\"rock\" fill=\"none\" stroke=\"#787878\" stroke-width=\"0.2\" stroke-linejoin=\"round\">\n\
+             <title>rock</title>\n\
+             <polyline points=\"0,0 4,0 4,2\"/>\n\
+             </g>\n\
+             <g id=\"sensor-1\" fill=\"none\" stroke=\"#ff0000\" stroke-width=\"0.5\" stroke-linejoin=\"round\">\n\
+             <title>sensor &lt;1&gt;</title>\n\
+             <polygon points=\"2,-1 3,0 2,1 1,0\"/>\n\
+             <circle cx=\"2\" cy=\"0\" r=\"0.25\" fill=\"#ff0000\" stroke=\"none\"/>\n\
+             </g>\n\
+             </svg>\n"
+        );
+        assert_eq!(svg_id("  height 25 "), "height-25");
+        assert_eq!(svg_id("<>"), "layer");
+    }
+
+    #[test]
+    fn svg_y_up() {
+        let svg = Svg::new()
+            .orientation(Orientation::YUp)
+            .layer(Layer::new("path", WHITE).polyline([(0, 0), (0, 3)]))
+            .render();
+        assert!(svg.contains("viewBox=\"-1 -4 2 5\""));
+        assert!(svg.contains("<polyline points=\"0,0 0,-3\"/>"));
+        assert!(Svg::new().render().contains("viewBox=\"-1 -1 2 2\""));
+    }
+
+    #[test]
+    fn headless_animation() {
+        let mut count = 0;
+        let mut animator = Animator::headless(vec![]);
+        let shown = animator
+            .run(|| {
+                count += 1;
+                (count <= 3).then(|| format!("#{}\n", count))
+            })
+            .unwrap();
+        assert_eq!(shown, 3);
+        assert_eq!(
+            String::from_utf8(animator.into_inner()).unwrap(),
+            "Frame 1\n#1\nFrame 2\n#2\nFrame 3\n#3\n"
+        );
+    }
+
+    #[test]
+    fn animation_frame_limit() {
+        let mut animator = Animator::headless(vec![]).max_frames(2);
+        assert_eq!(animator.run(|| Some(String::from("."))).unwrap(), 2);
+    }
+
+    #[test]
+    fn controls() {
+        assert_eq!(Control::parse(""), Some(Control::TogglePause));
+        assert_eq!(Control::parse(" s\n"), Some(Control::Step));
+        assert_eq!(Control::parse("q"), Some(Control::Quit));
+        assert_eq!(Control::parse("x"), None);
+    }
+
+    #[test]
+    fn paused_animation_steps() {
+        let (sender, receiver) = mpsc::channel();
+        let mut animator = Animator {
+            out: vec![],
+            delay: Some(Duration::from_millis(1)),
+            paused: true,
+            controls: Some(receiver),
+            max_frames: None,
+        };
+        for control in [
+            Control::Step,
+            Control::TogglePause,
+            Control::TogglePause,
+            Control::Quit,
+        ] {
+            sender.send(control).unwrap();
+        }
+        // Frame 1 waits for the step to frame 2, unpausing shows frame 3, which
+        // pauses again and then quits
+        let mut count = 0;
+        let shown = animator
+            .run(|| {
+                count += 1;
+                Some(count.to_string())
+            })
+            .unwrap();
+        assert_eq!(shown, 3);
+        let output = String::from_utf8(animator.into_inner()).unwrap();
+        assert!(output.starts_with(CLEAR_SCREEN));
+        assert!(output.contains(&format!("{}1\nFrame 1 (paused)", CURSOR_HOME)));
+    }
+
+    #[test]
+    fn single_voxel_mesh() {
+        let mesh = Mesh::from_voxels([[0, 0, 0]]);
+        assert_eq!(mesh.vertices().len(), 8);
+        assert_eq!(mesh.faces().len(), 6);
+        assert_eq!(mesh.area(None), 6);
+        // Every face points away from the center of the cube
+        for face in mesh.faces() {
+            let [a, b, _, d] = face.vertices.map(|i| mesh.vertices()[i].map(|c| c * 2 - 1));
+            let (ab, ad) = (sub3(b, a), sub3(d, a));
+            let normal = [
+                ab[1] * ad[2] - ab[2] * ad[1],
+                ab[2] * ad[0] - ab[0] * ad[2],
+                ab[0] * ad[1] - ab[1] * ad[0],
+            ];
+            assert!((0..3).map(|axis| normal[axis] * a[axis]).sum::<i64>() > 0);
+        }
+    }
+
+    fn sub3(a: [i64; 3], b: [i64; 3]) -> [i64; 3] {
+        [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+    }
+
+    #[test]
+    fn hidden_faces_and_pockets() {
+        // 3x3x3 cube with its center missing
+        let voxels = (0..27)
+            .map(|i| [i / 9, i / 3 % 3, i % 3])
+            .filter(|voxel| *voxel != [1, 1, 1])
+            .collect::<Vec<_>>();
+        let mesh = Mesh::from_voxels(voxels);
+        assert_eq!(mesh.area(Some(true)), 54);
+        assert_eq!(mesh.area(Some(false)), 6);
+
+        let boxes = Mesh::from_boxes(&[
+            Cuboid::new([0, 0, 0], [1000, 1000, 1000]),
+            Cuboid::new([500, 0, 0], [2000, 1000, 1000]),
+        ]);
+        assert_eq!(boxes.area(None), 2 * (2000 * 1000 * 2 + 1000 * 1000));
+        assert!(boxes.faces().iter().all(|face| face.exterior));
+    }
+
+    #[test]
+    fn mesh_formats() {
+        let mesh = Mesh::from_voxels([[0, 0, 0]]);
+        let colors = SurfaceColors {
+            exterior: WHITE,
+            interior: RED,
+        };
+        let obj = mesh.to_obj("cube.mtl");
+        assert!(obj.starts_with("mtllib cube.mtl\nv "));
+        assert_eq!(obj.matches("\nv ").count(), 8);
+        assert_eq!(obj.matches("\nf ").count(), 6);
+        assert!(obj.contains("usemtl exterior\n"));
+        assert_eq!(
+            Mesh::to_mtl(&colors),
+            "newmtl exterior\nKd 1.000 1.000 1.000\nnewmtl interior\nKd 1.000 0.000 0.000\n"
+        );
+        let ply = mesh.to_ply(&colors);
+        assert!(ply.contains("element vertex 8\n"));
+        assert!(ply.contains("element face 6\n"));
+        assert_eq!(
+            ply.lines()
+                .filter(|line| line.ends_with(" 255 255 255"))
+                .count(),
+            6
+        );
+
+        let directory = std::env::temp_dir().join(format!("aoc_mesh_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        mesh.save(directory.join("cube.obj"), &colors).unwrap();
+        assert_eq!(fs::read_to_string(directory.join("cube.obj")).unwrap(), obj);
+        assert!(directory.join("cube.mtl").exists());
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn numbered_frames() {
+        let directory = std::env::temp_dir().join(format!("aoc_frames_{}", std::process::id()));
+        let mut frames = Frames::new(&directory, "test", ImageFormat::Ppm);
+        let image = Image::new(1, 1, WHITE);
+        assert_eq!(
+            frames.write(&image).unwrap(),
+            directory.join("test_00000.ppm")
+        );
+        assert_eq!(
+            frames.write(&image).unwrap(),
+            directory.join("test_00001.ppm")
+        );
+        assert_eq!(frames.len(), 2);
+        assert_eq!(
+            fs::read(directory.join("test_00001.ppm")).unwrap(),
+            image.to_ppm()
+        );
+        fs::remove_dir_all(directory).unwrap();
+    }
 }
@@ -0,0 +1,408 @@
+#![allow(unused)]
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+use itertools::Itertools;
+
+// Decides which nodes a path may enter next.
+// `State` is everything the policy remembers about the path so far (e.g. the visited nodes),
+// a node may be entered if `visit` returns the state after entering it.
+pub trait VisitPolicy<Node> {
+    type State: Clone + Eq + Hash;
+
+    fn start(&self, start: &Node) -> Self::State;
+    fn visit(&self, state: &Self::State, node: &Node) -> Option<Self::State>;
+
+    // The number of ways to continue a path must only depend on the current node and `State`
+    // for the counts to be cached. Policies that look at anything else have to opt out.
+    fn memoize(&self) -> bool {
+        true
+    }
+}
+
+// Every node at most once.
+pub struct Simple;
+
+impl<Node> VisitPolicy<Node> for Simple
+where
+    Node: Ord + Clone + Hash,
+{
+    type State = BTreeSet<Node>;
+
+    fn start(&self, start: &Node) -> Self::State {
+        BTreeSet::from([start.clone()])
+    }
+
+    fn visit(&self, state: &Self::State, node: &Node) -> Option<Self::State> {
+        if state.contains(node) {
+            None
+        } else {
+            let mut next = state.clone();
+            next.insert(node.clone());
+            Some(next)
+        }
+    }
+}
+
+// Number of paths from `start` to `end` allowed by `policy`. Paths stop as soon as they reach `end`.
+pub fn count_paths<Node, I, P>(
+    neighbors: impl Fn(&Node) -> I,
+    start: &Node,
+    end: &Node,
+    policy: &P,
+) -> usize
+where
+    Node: Eq + Hash + Clone,
+    I: IntoIterator<Item = Node>,
+    P: VisitPolicy<Node>,
+{
+    fn count_rec<Node, I, P>(
+        neighbors: &impl Fn(&Node) -> I,
+        current: &Node,
+        end: &Node,
+        state: P::State,
+        policy: &P,
+        cache: &mut HashMap<(Node, P::State), usize>,
+    ) -> usize
+    where
+        Node: Eq + Hash + Clone,
+        I: IntoIterator<Item = Node>,
+        P: VisitPolicy<Node>,
+    {
+        if current == end {
+            return 1;
+        }
+        let cache_key = (current.clone(), state);
+        if let Some(paths) = cache.get(&cache_key) {
+            return *paths;
+        }
+        let paths = neighbors(current)
+            .into_iter()
+            .filter_map(|next| {
+                let next_state = policy.visit(&cache_key.1, &next)?;
+                Some(count_rec(neighbors, &next, end, next_state, policy, cache))
+            })
+            .sum();
+        if policy.memoize() {
+            cache.insert(cache_key, paths);
+        }
+        paths
+    }
+
+    count_rec(
+        &neighbors,
+        start,
+        end,
+        policy.start(start),
+        policy,
+        &mut HashMap::new(),
+    )
+}
+
+// All paths from `start` to `end` allowed by `policy`, each one including both ends.
+pub fn all_paths<Node, I, P>(
+    neighbors: impl Fn(&Node) -> I,
+    start: &Node,
+    end: &Node,
+    policy: &P,
+) -> Vec<Vec<Node>>
+where
+    Node: Eq + Clone,
+    I: IntoIterator<Item = Node>,
+    P: VisitPolicy<Node>,
+{
+    fn paths_rec<Node, I, P>(
+        neighbors: &impl Fn(&Node) -> I,
+        path: &mut Vec<Node>,
+        end: &Node,
+        state: P::State,
+        policy: &P,
+        paths: &mut Vec<Vec<Node>>,
+    ) where
+        Node: Eq + Clone,
+        I: IntoIterator<Item = Node>,
+        P: VisitPolicy<Node>,
+    {
+        let current = path.last().expect("Path starts with `start`").clone();
+        if current == *end {
+            paths.push(path.clone());
+            return;
+        }
+        for next in neighbors(&current) {
+            if let Some(next_state) = policy.visit(&state, &next) {
+                path.push(next);
+                paths_rec(neighbors, path, end, next_state, policy, paths);
+                path.pop();
+            }
+        }
+    }
+
+    let mut paths = vec![];
+    paths_rec(
+        &neighbors,
+        &mut vec![start.clone()],
+        end,
+        policy.start(start),
+        policy,
+        &mut paths,
+    );
+    paths
+}
+
+// The nodes of a cycle in the order they are walked, the first node is repeated at the end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<Node>(pub Vec<Node>);
+
+impl<Node: Debug> fmt::Display for Cycle<Node> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Cycle: {}",
+            self.0.iter().map(|node| format!("{:?}", node)).join(" -> ")
+        )
+    }
+}
+
+#[derive(PartialEq)]
+enum Mark {
+    Active,
+    Done,
+}
+
+fn topological_visit<Node, I>(
+    node: &Node,
+    successors: &impl Fn(&Node) -> I,
+    marks: &mut HashMap<Node, Mark>,
+    path: &mut Vec<Node>,
+    order: &mut Vec<Node>,
+) -> Result<(), Cycle<Node>>
+where
+    Node: Eq + Hash + Clone,
+    I: IntoIterator<Item = Node>,
+{
+    match marks.get(node) {
+        Some(Mark::Done) => return Ok(()),
+        Some(Mark::Active) => {
+            let begin = path.iter().position(|other| other == node).unwrap();
+            let mut cycle = path[begin..].to_vec();
+            cycle.push(node.clone());
+            return Err(Cycle(cycle));
+        }
+        None => {}
+    }
+    marks.insert(node.clone(), Mark::Active);
+    path.push(node.clone());
+    for next in successors(node) {
+        topological_visit(&next, successors, marks, path, order)?;
+    }
+    path.pop();
+    marks.insert(node.clone(), Mark::Done);
+    order.push(node.clone());
+    Ok(())
+}
+
+// All nodes reachable from `roots`, ordered such that every node comes after all of its successors.
+// Fails with the first cycle found.
+pub fn topological_sort<Node, I>(
+    roots: impl IntoIterator<Item = Node>,
+    successors: impl Fn(&Node) -> I,
+) -> Result<Vec<Node>, Cycle<Node>>
+where
+    Node: Eq + Hash + Clone,
+    I: IntoIterator<Item = Node>,
+{
+    let mut marks = HashMap::new();
+    let mut order = vec![];
+    for root in roots {
+        topological_visit(&root, &successors, &mut marks, &mut vec![], &mut order)?;
+    }
+    Ok(order)
+}
+
+// All nodes reachable from `roots` that can reach `target`, `target` itself excluded.
+pub fn reverse_reachable<Node, I>(
+    roots: impl IntoIterator<Item = Node>,
+    successors: impl Fn(&Node) -> I,
+    target: &Node,
+) -> HashSet<Node>
+where
+    Node: Eq + Hash + Clone,
+    I: IntoIterator<Item = Node>,
+{
+    let mut predecessors = HashMap::<Node, Vec<Node>>::new();
+    let mut seen = HashSet::new();
+    let mut queue = roots.into_iter().collect::<VecDeque<_>>();
+    while let Some(node) = queue.pop_front() {
+        if !seen.insert(node.clone()) {
+            continue;
+        }
+        for next in successors(&node) {
+            predecessors
+                .entry(next.clone())
+                .or_default()
+                .push(node.clone());
+            queue.push_back(next);
+        }
+    }
+
+    let mut reaching = HashSet::new();
+    let mut queue = VecDeque::from([target.clone()]);
+    while let Some(node) = queue.pop_front() {
+        for previous in predecessors.get(&node).into_iter().flatten() {
+            if reaching.insert(previous.clone()) {
+                queue.push_back(previous.clone());
+            }
+        }
+    }
+    reaching.remove(target);
+    reaching
+}
+
+// Bottom-up evaluation of a DAG: `fold` gets each node together with the values of its
+// successors (in the order `successors` returns them) and computes the node's value.
+pub fn evaluate<Node, I, T>(
+    roots: impl IntoIterator<Item = Node>,
+    successors: impl Fn(&Node) -> I,
+    mut fold: impl FnMut(&Node, Vec<&T>) -> T,
+) -> Result<HashMap<Node, T>, Cycle<Node>>
+where
+    Node: Eq + Hash + Clone,
+    I: IntoIterator<Item = Node>,
+{
+    let mut values = HashMap::new();
+    for node in topological_sort(roots, &successors)? {
+        let value = {
+            let children = successors(&node)
+                .into_iter()
+                .map(|child| &values[&child])
+                .collect_vec();
+            fold(&node, children)
+        };
+        values.insert(node, value);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    //  0 --- 1 --- 3
+    //  |     |     |
+    //  +---- 2 ----+
+    fn neighbors(node: &i32) -> Vec<i32> {
+        match node {
+            0 => vec![1, 2],
+            1 => vec![0, 2, 3],
+            2 => vec![0, 1, 3],
+            3 => vec![1, 2],
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn simple_paths() {
+        let mut paths = all_paths(neighbors, &0, &3, &Simple);
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                vec![0, 1, 2, 3],
+                vec![0, 1, 3],
+                vec![0, 2, 1, 3],
+                vec![0, 2, 3]
+            ]
+        );
+        assert_eq!(count_paths(neighbors, &0, &3, &Simple), 4);
+    }
+
+    // Node 1 may be visited up to twice
+    struct RevisitOne;
+
+    impl VisitPolicy<i32> for RevisitOne {
+        type State = (BTreeSet<i32>, bool);
+
+        fn start(&self, start: &i32) -> Self::State {
+            (BTreeSet::from([*start]), false)
+        }
+
+        fn visit(&self, state: &Self::State, node: &i32) -> Option<Self::State> {
+            let (visited, revisited) = state;
+            match (visited.contains(node), *node == 1 && !revisited) {
+                (false, _) => {
+                    let mut visited = visited.clone();
+                    visited.insert(*node);
+                    Some((visited, *revisited))
+                }
+                (true, true) => Some((visited.clone(), true)),
+                (true, false) => None,
+            }
+        }
+    }
+
+    #[test]
+    fn custom_policy() {
+        let paths = all_paths(neighbors, &0, &3, &RevisitOne);
+        assert!(paths.contains(&vec![0, 1, 2, 1, 3]));
+        assert_eq!(paths.len(), count_paths(neighbors, &0, &3, &RevisitOne));
+        assert_eq!(paths.len(), 5);
+    }
+
+    //  a --> b --> d
+    //  |           ^
+    //  +---> c ----+
+    fn dag(node: &char) -> Vec<char> {
+        match node {
+            'a' => vec!['b', 'c'],
+            'b' | 'c' => vec!['d'],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn topological_order() {
+        let order = topological_sort(['a'], dag).unwrap();
+        assert_eq!(order.len(), 4);
+        let position = |node| order.iter().position(|other| *other == node).unwrap();
+        assert!(position('d') < position('b'));
+        assert!(position('d') < position('c'));
+        assert!(position('b') < position('a'));
+        assert!(position('c') < position('a'));
+    }
+
+    #[test]
+    fn cycle_is_named() {
+        let cyclic = |node: &char| match node {
+            'a' => vec!['b'],
+            'b' => vec!['c'],
+            'c' => vec!['a'],
+            _ => vec![],
+        };
+        let cycle = topological_sort(['a', 'd'], cyclic).unwrap_err();
+        assert_eq!(cycle, Cycle(vec!['a', 'b', 'c', 'a']));
+        assert_eq!(cycle.to_string(), "Cycle: 'a' -> 'b' -> 'c' -> 'a'");
+    }
+
+    #[test]
+    fn reverse_reachability() {
+        let reaching = reverse_reachable(['a', 'b', 'c', 'd'], dag, &'d');
+        assert_eq!(reaching, HashSet::from(['a', 'b', 'c']));
+        assert!(reverse_reachable(['a'], dag, &'a').is_empty());
+    }
+
+    #[test]
+    fn evaluate_counts_paths() {
+        let paths = evaluate(['a'], dag, |node, children: Vec<&usize>| {
+            if children.is_empty() {
+                1
+            } else {
+                children.into_iter().sum()
+            }
+        })
+        .unwrap();
+        assert_eq!(paths[&'a'], 2);
+        assert_eq!(paths[&'d'], 1);
+    }
+}
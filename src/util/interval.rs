@@ -0,0 +1,238 @@
+#![allow(unused)]
+use std::ops::Range;
+
+use itertools::Itertools;
+use num::{CheckedAdd, Integer};
+
+// Set of integers stored as sorted, disjoint and non touching half-open ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Integer + Copy,
+{
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet { ranges: vec![] }
+    }
+
+    pub fn from_ranges(ranges: impl IntoIterator<Item = Range<T>>) -> IntervalSet<T> {
+        let mut set = IntervalSet::new();
+        set.ranges = ranges
+            .into_iter()
+            .filter(|range| !range.is_empty())
+            .collect();
+        set.normalize();
+        set
+    }
+
+    // Both ends included, e.g. (1, 3) contains 1, 2 and 3. The ranges are stored
+    // half-open, so an end of T::MAX can not be represented and panics.
+    pub fn from_inclusive(intervals: impl IntoIterator<Item = (T, T)>) -> IntervalSet<T>
+    where
+        T: CheckedAdd,
+    {
+        IntervalSet::from_ranges(
+            intervals
+                .into_iter()
+                .map(|(start, end)| start..exclusive_end(end)),
+        )
+    }
+
+    fn normalize(&mut self) {
+        self.ranges.sort_by_key(|range| range.start);
+        let mut merged: Vec<Range<T>> = Vec::with_capacity(self.ranges.len());
+        for range in self.ranges.drain(..) {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        self.ranges = merged;
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        if !range.is_empty() {
+            self.ranges.push(range);
+            self.normalize();
+        }
+    }
+
+    // Panics for an end of T::MAX, like from_inclusive
+    pub fn insert_inclusive(&mut self, start: T, end: T)
+    where
+        T: CheckedAdd,
+    {
+        self.insert(start..exclusive_end(end));
+    }
+
+    pub fn remove(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        self.ranges = self
+            .ranges
+            .drain(..)
+            .flat_map(|existing| {
+                let before = existing.start..existing.end.min(range.start);
+                let after = existing.start.max(range.end)..existing.end;
+                [before, after]
+            })
+            .filter(|range| !range.is_empty())
+            .collect();
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        IntervalSet::from_ranges(self.ranges.iter().chain(other.ranges.iter()).cloned())
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let overlap = a.start.max(b.start)..a.end.min(b.end);
+            if !overlap.is_empty() {
+                ranges.push(overlap);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut difference = self.clone();
+        for range in other.ranges.iter() {
+            difference.remove(range.clone());
+        }
+        difference
+    }
+
+    pub fn contains(&self, point: T) -> bool {
+        let index = self.ranges.partition_point(|range| range.end <= point);
+        self.ranges
+            .get(index)
+            .is_some_and(|range| range.contains(&point))
+    }
+
+    // Total number of integers covered
+    pub fn len(&self) -> T {
+        self.ranges
+            .iter()
+            .fold(T::zero(), |len, range| len + range.end - range.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn ranges(&self) -> impl Iterator<Item = &Range<T>> {
+        self.ranges.iter()
+    }
+
+    // The holes between the first and the last range
+    pub fn gaps(&self) -> impl Iterator<Item = Range<T>> + '_ {
+        self.ranges
+            .iter()
+            .tuple_windows()
+            .map(|(before, after)| before.end..after.start)
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|range| range.start)
+    }
+
+    // Largest integer contained, i.e. the inclusive end
+    pub fn max(&self) -> Option<T> {
+        self.ranges.last().map(|range| range.end - T::one())
+    }
+}
+
+impl<T> From<Range<T>> for IntervalSet<T>
+where
+    T: Integer + Copy,
+{
+    fn from(range: Range<T>) -> Self {
+        IntervalSet::from_ranges([range])
+    }
+}
+
+fn exclusive_end<T: Integer + CheckedAdd>(end: T) -> T {
+    end.checked_add(&T::one())
+        .expect("Inclusive end has to be below the maximum of its type")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn merge_overlapping_and_touching() {
+        let set = IntervalSet::from_ranges([5..8, 1..3, 2..4, 8..9]);
+        assert_eq!(set.ranges().cloned().collect_vec(), vec![1..4, 5..9]);
+        assert_eq!(set.len(), 7);
+        assert_eq!(set.gaps().collect_vec(), vec![4..5]);
+        assert_eq!(
+            IntervalSet::from_inclusive([(1, 3), (4, 4)]),
+            IntervalSet::from(1..5)
+        );
+    }
+
+    #[test]
+    fn point_queries() {
+        let set = IntervalSet::from_inclusive([(-5, -1), (3, 3)]);
+        assert!(set.contains(-5));
+        assert!(set.contains(-1));
+        assert!(!set.contains(0));
+        assert!(set.contains(3));
+        assert!(!set.contains(4));
+        assert_eq!((set.min(), set.max()), (Some(-5), Some(3)));
+        assert!(!IntervalSet::<i32>::new().contains(0));
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut set = IntervalSet::new();
+        set.insert(0..10);
+        set.remove(3..5);
+        set.remove(9..20);
+        assert_eq!(set, IntervalSet::from_ranges([0..3, 5..9]));
+        set.insert_inclusive(3, 4);
+        assert_eq!(set, IntervalSet::from(0..9));
+        set.remove(-10..10);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn inclusive_up_to_max() {
+        let set = IntervalSet::from_inclusive([(250_u8, 254)]);
+        assert!(set.contains(254));
+        assert!(!set.contains(255));
+    }
+
+    #[test]
+    #[should_panic(expected = "Inclusive end has to be below the maximum of its type")]
+    fn inclusive_end_at_max() {
+        IntervalSet::new().insert_inclusive(0, u8::MAX);
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = IntervalSet::from_ranges([0..5, 10..15]);
+        let b = IntervalSet::from_ranges([3..12, 14..20]);
+        assert_eq!(a.union(&b), IntervalSet::from(0..20));
+        assert_eq!(
+            a.intersection(&b),
+            IntervalSet::from_ranges([3..5, 10..12, 14..15])
+        );
+        assert_eq!(a.difference(&b), IntervalSet::from_ranges([0..3, 12..14]));
+        assert_eq!(b.difference(&a), IntervalSet::from_ranges([5..10, 15..20]));
+    }
+}
@@ -0,0 +1,141 @@
+#![allow(unused)]
+use num::{Integer, Signed};
+
+pub type Point<T> = (T, T);
+
+// Every lattice point on the segment from `from` to `to`, both ends included.
+// Works for any slope: the step is the direction divided by the gcd of its
+// components, so e.g. (0, 0) -> (4, 2) yields (0, 0), (2, 1) and (4, 2).
+#[derive(Debug, Clone)]
+pub struct LinePoints<T> {
+    current: Point<T>,
+    step: Point<T>,
+    remaining: T,
+    done: bool,
+}
+
+impl<T> Iterator for LinePoints<T>
+where
+    T: Integer + Signed + Copy,
+{
+    type Item = Point<T>;
+
+    fn next(&mut self) -> Option<Point<T>> {
+        if self.done {
+            return None;
+        }
+        let point = self.current;
+        if self.remaining.is_zero() {
+            self.done = true;
+        } else {
+            self.current = (point.0 + self.step.0, point.1 + self.step.1);
+            self.remaining = self.remaining - T::one();
+        }
+        Some(point)
+    }
+}
+
+pub fn line_points<T>(from: Point<T>, to: Point<T>) -> LinePoints<T>
+where
+    T: Integer + Signed + Copy,
+{
+    let delta = (to.0 - from.0, to.1 - from.1);
+    let steps = delta.0.gcd(&delta.1);
+    let step = if steps.is_zero() {
+        (T::zero(), T::zero())
+    } else {
+        (delta.0 / steps, delta.1 / steps)
+    };
+    LinePoints {
+        current: from,
+        step,
+        remaining: steps,
+        done: false,
+    }
+}
+
+// Lattice points of consecutive segments through `vertices`. Shared corners are
+// only yielded once, a single vertex yields just itself.
+pub fn polyline_points<T>(vertices: &[Point<T>]) -> impl Iterator<Item = Point<T>> + '_
+where
+    T: Integer + Signed + Copy,
+{
+    vertices.first().copied().into_iter().chain(
+        vertices
+            .windows(2)
+            .flat_map(|segment| line_points(segment[0], segment[1]).skip(1)),
+    )
+}
+
+pub fn is_axis_aligned<T: PartialEq>(from: &Point<T>, to: &Point<T>) -> bool {
+    from.0 == to.0 || from.1 == to.1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+    use test_log::test;
+
+    #[test]
+    fn horizontal_and_vertical() {
+        assert_eq!(
+            line_points((1, 1), (3, 1)).collect_vec(),
+            vec![(1, 1), (2, 1), (3, 1)]
+        );
+        assert_eq!(
+            line_points((7, 9), (7, 7)).collect_vec(),
+            vec![(7, 9), (7, 8), (7, 7)]
+        );
+    }
+
+    #[test]
+    fn diagonal() {
+        assert_eq!(
+            line_points((9, 7), (7, 9)).collect_vec(),
+            vec![(9, 7), (8, 8), (7, 9)]
+        );
+    }
+
+    #[test]
+    fn arbitrary_slope() {
+        assert_eq!(
+            line_points((0, 0), (6, -4)).collect_vec(),
+            vec![(0, 0), (3, -2), (6, -4)]
+        );
+        assert_eq!(
+            line_points((0, 0), (2, 5)).collect_vec(),
+            vec![(0, 0), (2, 5)]
+        );
+    }
+
+    #[test]
+    fn single_point() {
+        assert_eq!(line_points((4, 2), (4, 2)).collect_vec(), vec![(4, 2)]);
+    }
+
+    #[test]
+    fn reversed_is_mirrored() {
+        let forward = line_points((-3i64, 5), (9, -1)).collect_vec();
+        let mut backward = line_points((9i64, -1), (-3, 5)).collect_vec();
+        backward.reverse();
+        assert_eq!(forward, backward);
+    }
+
+    #[test]
+    fn polyline() {
+        assert_eq!(
+            polyline_points(&[(498, 4), (498, 6), (496, 6)]).collect_vec(),
+            vec![(498, 4), (498, 5), (498, 6), (497, 6), (496, 6)]
+        );
+        assert_eq!(polyline_points(&[(1, 1)]).collect_vec(), vec![(1, 1)]);
+        assert_eq!(polyline_points::<i32>(&[]).count(), 0);
+    }
+
+    #[test]
+    fn axis_aligned() {
+        assert!(is_axis_aligned(&(1, 2), &(1, 5)));
+        assert!(is_axis_aligned(&(1, 2), &(4, 2)));
+        assert!(!is_axis_aligned(&(1, 2), &(2, 3)));
+    }
+}
@@ -0,0 +1,343 @@
+#![allow(unused)]
+use std::{collections::HashMap, fmt::Debug, hash::Hash};
+
+use itertools::Itertools;
+use num::{CheckedAdd, CheckedMul, Unsigned};
+
+// Numbers a matrix can hold, implemented for all unsigned integer types
+pub trait Entry: Unsigned + CheckedAdd + CheckedMul + PartialOrd + Copy + Debug {}
+
+impl<T> Entry for T where T: Unsigned + CheckedAdd + CheckedMul + PartialOrd + Copy + Debug {}
+
+// Square matrix over unsigned integers, meant for u64 and u128. Without a
+// modulus every operation panics on overflow instead of wrapping silently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix<T> {
+    rows: Vec<Vec<T>>,
+}
+
+impl<T> Matrix<T>
+where
+    T: Entry,
+{
+    pub fn zeros(size: usize) -> Matrix<T> {
+        Matrix {
+            rows: vec![vec![T::zero(); size]; size],
+        }
+    }
+
+    pub fn identity(size: usize) -> Matrix<T> {
+        let mut matrix = Matrix::zeros(size);
+        for i in 0..size {
+            matrix.rows[i][i] = T::one();
+        }
+        matrix
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Matrix<T> {
+        assert!(
+            rows.iter().all(|row| row.len() == rows.len()),
+            "Matrix has to be square"
+        );
+        Matrix { rows }
+    }
+
+    pub fn size(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> T {
+        self.rows[row][col]
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: T) {
+        self.rows[row][col] = value;
+    }
+
+    pub fn mul(&self, other: &Matrix<T>) -> Matrix<T> {
+        self.mul_impl(other, None)
+    }
+
+    pub fn mul_mod(&self, other: &Matrix<T>, modulus: T) -> Matrix<T> {
+        self.mul_impl(other, Some(modulus))
+    }
+
+    // self^exponent by repeated squaring, O(size^3 * log(exponent))
+    pub fn pow(&self, exponent: u64) -> Matrix<T> {
+        self.pow_impl(exponent, None)
+    }
+
+    pub fn pow_mod(&self, exponent: u64, modulus: T) -> Matrix<T> {
+        self.pow_impl(exponent, Some(modulus))
+    }
+
+    // Matrix times column vector
+    pub fn apply(&self, vector: &[T]) -> Vec<T> {
+        self.apply_impl(vector, None)
+    }
+
+    pub fn apply_mod(&self, vector: &[T], modulus: T) -> Vec<T> {
+        self.apply_impl(vector, Some(modulus))
+    }
+
+    fn mul_impl(&self, other: &Matrix<T>, modulus: Option<T>) -> Matrix<T> {
+        assert_eq!(self.size(), other.size(), "Matrix sizes differ");
+        let size = self.size();
+        let mut result = Matrix::zeros(size);
+        for row in 0..size {
+            for k in 0..size {
+                let factor = self.rows[row][k];
+                if factor.is_zero() {
+                    continue;
+                }
+                for col in 0..size {
+                    let product = multiply(factor, other.rows[k][col], modulus);
+                    result.rows[row][col] = add(result.rows[row][col], product, modulus);
+                }
+            }
+        }
+        result
+    }
+
+    fn pow_impl(&self, mut exponent: u64, modulus: Option<T>) -> Matrix<T> {
+        let mut result = Matrix::identity(self.size());
+        if let Some(modulus) = modulus {
+            result = result.reduce(modulus);
+        }
+        let mut base = self.clone();
+        if let Some(modulus) = modulus {
+            base = base.reduce(modulus);
+        }
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul_impl(&base, modulus);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.mul_impl(&base, modulus);
+            }
+        }
+        result
+    }
+
+    fn apply_impl(&self, vector: &[T], modulus: Option<T>) -> Vec<T> {
+        assert_eq!(self.size(), vector.len(), "Vector size differs");
+        self.rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(vector)
+                    .fold(T::zero(), |sum, (&factor, &value)| {
+                        add(sum, multiply(factor, value, modulus), modulus)
+                    })
+            })
+            .collect()
+    }
+
+    fn reduce(&self, modulus: T) -> Matrix<T> {
+        Matrix {
+            rows: self
+                .rows
+                .iter()
+                .map(|row| row.iter().map(|&value| value % modulus).collect())
+                .collect(),
+        }
+    }
+}
+
+fn add<T: Entry>(a: T, b: T, modulus: Option<T>) -> T {
+    match modulus {
+        // Both are below the modulus, so a + b only overflows if a >= modulus - b
+        Some(modulus) => {
+            if a >= modulus - b {
+                a - (modulus - b)
+            } else {
+                a + b
+            }
+        }
+        None => a.checked_add(&b).expect("Matrix entry overflowed"),
+    }
+}
+
+fn multiply<T: Entry>(a: T, b: T, modulus: Option<T>) -> T {
+    match modulus {
+        Some(modulus) => match a.checked_mul(&b) {
+            Some(product) => product % modulus,
+            None => {
+                // Double and add, slower but add never overflows
+                let two = T::one() + T::one();
+                let (mut a, mut b, mut product) = (a % modulus, b, T::zero());
+                while !b.is_zero() {
+                    if (b % two).is_one() {
+                        product = add(product, a, Some(modulus));
+                    }
+                    a = add(a, a, Some(modulus));
+                    b = b / two;
+                }
+                product
+            }
+        },
+        None => a.checked_mul(&b).expect("Matrix entry overflowed"),
+    }
+}
+
+// Linear system over counts of states: each step every state turns into a
+// fixed number of other states, e.g. a lanternfish with timer 0 becomes one
+// with timer 6 and one with timer 8.
+#[derive(Debug, Clone)]
+pub struct CountTransitions<State, T> {
+    states: Vec<State>,
+    index: HashMap<State, usize>,
+    matrix: Matrix<T>,
+}
+
+impl<State, T> CountTransitions<State, T>
+where
+    State: Hash + Eq + Clone + Debug,
+    T: Entry,
+{
+    // `transitions` lists what one unit of a state becomes after a single step.
+    // Every state it mentions has to be part of `states`.
+    pub fn new(
+        states: impl IntoIterator<Item = State>,
+        transitions: impl Fn(&State) -> Vec<(State, T)>,
+    ) -> CountTransitions<State, T> {
+        let states = states.into_iter().unique().collect_vec();
+        let index: HashMap<State, usize> = states
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(i, state)| (state, i))
+            .collect();
+        let mut matrix = Matrix::zeros(states.len());
+        for (from, state) in states.iter().enumerate() {
+            for (next, count) in transitions(state) {
+                let to = *index
+                    .get(&next)
+                    .unwrap_or_else(|| panic!("Unknown state {:?}", next));
+                matrix.set(to, from, add(matrix.get(to, from), count, None));
+            }
+        }
+        CountTransitions {
+            states,
+            index,
+            matrix,
+        }
+    }
+
+    pub fn matrix(&self) -> &Matrix<T> {
+        &self.matrix
+    }
+
+    // Counts after `steps` steps, states with a count of zero are left out
+    pub fn after(&self, counts: &HashMap<State, T>, steps: u64) -> HashMap<State, T> {
+        self.collect(self.matrix.pow(steps).apply(&self.vector(counts)))
+    }
+
+    pub fn after_mod(
+        &self,
+        counts: &HashMap<State, T>,
+        steps: u64,
+        modulus: T,
+    ) -> HashMap<State, T> {
+        let vector = self.vector(counts);
+        self.collect(
+            self.matrix
+                .pow_mod(steps, modulus)
+                .apply_mod(&vector, modulus),
+        )
+    }
+
+    fn vector(&self, counts: &HashMap<State, T>) -> Vec<T> {
+        let mut vector = vec![T::zero(); self.states.len()];
+        for (state, &count) in counts {
+            let i = *self
+                .index
+                .get(state)
+                .unwrap_or_else(|| panic!("Unknown state {:?}", state));
+            vector[i] = add(vector[i], count, None);
+        }
+        vector
+    }
+
+    fn collect(&self, vector: Vec<T>) -> HashMap<State, T> {
+        self.states
+            .iter()
+            .cloned()
+            .zip(vector)
+            .filter(|(_, count)| !count.is_zero())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    fn fibonacci() -> Matrix<u64> {
+        Matrix::from_rows(vec![vec![1, 1], vec![1, 0]])
+    }
+
+    #[test]
+    fn power() {
+        assert_eq!(fibonacci().pow(0), Matrix::identity(2));
+        assert_eq!(fibonacci().pow(10).get(0, 1), 55);
+        assert_eq!(fibonacci().pow(90).get(0, 1), 2880067194370816120);
+    }
+
+    #[test]
+    #[should_panic(expected = "Matrix entry overflowed")]
+    fn overflow_panics() {
+        fibonacci().pow(100);
+    }
+
+    #[test]
+    fn modular_power() {
+        let modulus = 1_000_000_007;
+        assert_eq!(
+            fibonacci().pow_mod(90, modulus).get(0, 1),
+            2880067194370816120 % modulus
+        );
+        let huge = fibonacci().pow_mod(1_000_000_000_000, modulus);
+        let half = fibonacci().pow_mod(500_000_000_000, modulus);
+        assert_eq!(half.mul_mod(&half, modulus), huge);
+    }
+
+    #[test]
+    fn modular_u128_near_limit() {
+        let modulus = u128::MAX - 158;
+        let matrix = Matrix::<u128>::from_rows(vec![vec![modulus - 1, 2], vec![3, modulus - 2]]);
+        // (-1 2; 3 -2)^2 = (7 -6; -9 10)
+        assert_eq!(
+            matrix.pow_mod(2, modulus),
+            Matrix::from_rows(vec![vec![7, modulus - 6], vec![modulus - 9, 10]])
+        );
+    }
+
+    #[test]
+    fn apply() {
+        assert_eq!(fibonacci().apply(&[1, 0]), vec![1, 1]);
+        assert_eq!(fibonacci().pow(5).apply(&[1, 0]), vec![8, 5]);
+    }
+
+    #[test]
+    fn count_transitions() {
+        // Rabbit pairs: young ones grow up, adult ones stay and get a young pair
+        let rabbits =
+            CountTransitions::<&str, u64>::new(["young", "adult"], |state| match *state {
+                "young" => vec![("adult", 1)],
+                _ => vec![("adult", 1), ("young", 1)],
+            });
+        let start = HashMap::from([("young", 1)]);
+        assert_eq!(rabbits.after(&start, 0), start);
+        assert_eq!(
+            rabbits.after(&start, 10),
+            HashMap::from([("young", 34), ("adult", 55)])
+        );
+        assert_eq!(
+            rabbits.after_mod(&start, 10, 10),
+            HashMap::from([("young", 4), ("adult", 5)])
+        );
+    }
+}
@@ -1,6 +1,20 @@
+pub mod bits;
 pub mod bool_helper;
+pub mod boxes;
+pub mod components;
+pub mod cycle;
+pub mod draw;
+pub mod graph;
 pub mod index;
+pub mod interval;
+pub mod line;
+pub mod matrix;
+pub mod modular;
 pub mod multiple_sum_problem;
+pub mod ocr;
 pub mod parse;
+pub mod rotation;
+pub mod search;
 pub mod shortest_path;
-pub mod draw;
+pub mod vm;
+pub mod window;
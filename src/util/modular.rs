@@ -0,0 +1,160 @@
+#![allow(unused)]
+use num::Integer;
+
+pub fn gcd<T: Integer + Copy>(a: T, b: T) -> T {
+    if b.is_zero() {
+        if a < T::zero() {
+            T::zero() - a
+        } else {
+            a
+        }
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+pub fn lcm<T: Integer + Copy>(a: T, b: T) -> T {
+    if a.is_zero() || b.is_zero() {
+        return T::zero();
+    }
+    let lcm = a / gcd(a, b) * b;
+    if lcm < T::zero() {
+        T::zero() - lcm
+    } else {
+        lcm
+    }
+}
+
+// Least common multiple of all numbers, 1 for none
+pub fn lcm_all<T: Integer + Copy>(numbers: impl IntoIterator<Item = T>) -> T {
+    numbers.into_iter().fold(T::one(), lcm)
+}
+
+// Returns (g, x, y) with a * x + b * y = g = gcd(a, b)
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    if b == 0 {
+        if a < 0 {
+            (-a, -1, 0)
+        } else {
+            (a, 1, 0)
+        }
+    } else {
+        let (g, x, y) = extended_gcd(b, a.rem_euclid(b));
+        (g, y, x - a.div_euclid(b) * y)
+    }
+}
+
+// Representative of a in 0..modulus
+pub fn normalize(a: i64, modulus: i64) -> i64 {
+    assert!(modulus > 0, "Modulus has to be positive");
+    a.rem_euclid(modulus)
+}
+
+pub fn mul_mod(a: i64, b: i64, modulus: i64) -> i64 {
+    (a as i128 * b as i128).rem_euclid(modulus as i128) as i64
+}
+
+// base^exponent mod modulus by repeated squaring, intermediate products use i128
+pub fn pow_mod(base: i64, mut exponent: u64, modulus: i64) -> i64 {
+    let mut base = normalize(base, modulus);
+    let mut result = normalize(1, modulus);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exponent >>= 1;
+    }
+    result
+}
+
+// x with a * x = 1 mod modulus, None if a and modulus share a factor
+pub fn inverse_mod(a: i64, modulus: i64) -> Option<i64> {
+    let (g, x, _) = extended_gcd(normalize(a, modulus), modulus);
+    if g == 1 {
+        Some(normalize(x, modulus))
+    } else {
+        None
+    }
+}
+
+// Solves x = residue_i mod modulus_i for all given pairs. The moduli do not have
+// to be coprime, the result is (x, lcm of the moduli) with x in 0..lcm, or None
+// if the congruences contradict each other.
+pub fn crt(congruences: impl IntoIterator<Item = (i64, i64)>) -> Option<(i64, i64)> {
+    congruences
+        .into_iter()
+        .try_fold((0, 1), |(x, modulus), (residue, other_modulus)| {
+            let residue = normalize(residue, other_modulus);
+            let (g, p, _) = extended_gcd(modulus, other_modulus);
+            let difference = residue - x;
+            if difference % g != 0 {
+                return None;
+            }
+            let combined = modulus / g * other_modulus;
+            // x + modulus * k solves both if modulus * k = difference mod other_modulus
+            let step = other_modulus / g;
+            let k = mul_mod(difference / g, p, step);
+            let x = normalize(x + mul_mod(modulus, k, combined), combined);
+            Some((x, combined))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(-12, 18), 6);
+        assert_eq!(gcd(7u64, 0), 7);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(-4, 6), 12);
+        assert_eq!(lcm_all([2usize, 3, 4, 5]), 60);
+        assert_eq!(lcm_all(Vec::<i32>::new()), 1);
+    }
+
+    #[test]
+    fn extended() {
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+    }
+
+    #[test]
+    fn power() {
+        assert_eq!(pow_mod(2, 10, 1000), 24);
+        assert_eq!(pow_mod(-3, 3, 7), 1);
+        assert_eq!(pow_mod(5, 0, 1), 0);
+        // Fermat: a^(p-1) = 1 mod p
+        assert_eq!(pow_mod(123_456_789, 1_000_000_006, 1_000_000_007), 1);
+    }
+
+    #[test]
+    fn inverse() {
+        assert_eq!(inverse_mod(3, 11), Some(4));
+        assert_eq!(inverse_mod(-3, 11), Some(7));
+        assert_eq!(inverse_mod(6, 9), None);
+    }
+
+    #[test]
+    fn coprime_crt() {
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // Bus schedule 7,13,x,x,59,x,31,19: bus at index i leaves at t + i
+        let buses = [(0, 7), (1, 13), (4, 59), (6, 31), (7, 19)];
+        assert_eq!(
+            crt(buses.map(|(offset, bus)| (-offset, bus))),
+            Some((1068781, 7 * 13 * 59 * 31 * 19))
+        );
+    }
+
+    #[test]
+    fn non_coprime_crt() {
+        assert_eq!(crt([(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt([(1, 4), (2, 6)]), None);
+        assert_eq!(crt([(3, 10), (3, 15)]), Some((3, 30)));
+        assert_eq!(crt(Vec::new()), Some((0, 1)));
+    }
+}
@@ -1,4 +1,13 @@
-use std::collections::HashSet;
+#![allow(unused)]
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    ops::Range,
+};
+
+use itertools::Itertools;
+use num::Integer;
 
 pub fn two_sum<'a, T>(numbers: &'a [T], sum: &'a T) -> Vec<[T; 2]>
 where
@@ -28,16 +37,220 @@ where
         .collect()
 }
 
-pub fn three_sum(numbers: &[i32], sum: i32) -> Vec<[i32; 3]> {
-    let mut valid_numbers = HashSet::new();
-    for number in numbers {
-        let search = sum - number;
-        let found = two_sum(numbers, &search);
-        for pair in found {
-            valid_numbers.insert([*number, pair[0], pair[1]]);
+// Distinct sorted value triples of three different elements adding up to sum
+pub fn three_sum<T>(numbers: &[T], sum: T) -> Vec<[T; 3]>
+where
+    T: Integer + Hash + Copy,
+{
+    k_sum::<T, 3>(numbers, sum, Strategy::TwoPointer)
+        .into_iter()
+        .map(|indices| {
+            let mut values = indices.map(|i| numbers[i]);
+            values.sort();
+            values
+        })
+        .unique()
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    // Remembers the values seen so far, O(n^(k-1)) time and O(n) memory
+    Hash,
+    // Sorts once and closes in from both ends, O(n^(k-1)) time without hashing
+    TwoPointer,
+}
+
+// Every set of K different indices whose values add up to sum. Equal values at
+// different indices count as different elements, so [5, 5, 5] has three pairs
+// adding up to 10. Indices within a tuple are ascending, tuples are sorted.
+pub fn k_sum<T, const K: usize>(numbers: &[T], sum: T, strategy: Strategy) -> Vec<[usize; K]>
+where
+    T: Integer + Hash + Copy,
+{
+    let mut order = (0..numbers.len()).collect_vec();
+    if strategy == Strategy::TwoPointer {
+        order.sort_by_key(|&i| numbers[i]);
+    }
+    let mut found = vec![];
+    k_sum_from(numbers, &order, K, sum, strategy, &mut vec![], &mut found);
+    found
+        .into_iter()
+        .map(|mut indices| {
+            indices.sort();
+            indices.try_into().unwrap()
+        })
+        .sorted()
+        .collect()
+}
+
+// Picks the elements of a combination one by one from `order`, only looking
+// at later positions so that no index is used twice.
+fn k_sum_from<T>(
+    numbers: &[T],
+    order: &[usize],
+    k: usize,
+    sum: T,
+    strategy: Strategy,
+    chosen: &mut Vec<usize>,
+    found: &mut Vec<Vec<usize>>,
+) where
+    T: Integer + Hash + Copy,
+{
+    match k {
+        0 => {
+            if sum.is_zero() {
+                found.push(chosen.clone());
+            }
+        }
+        1 => found.extend(
+            order
+                .iter()
+                .filter(|&&i| numbers[i] == sum)
+                .map(|&i| [chosen.as_slice(), &[i]].concat()),
+        ),
+        2 => {
+            let pairs = match strategy {
+                Strategy::Hash => hash_pairs(numbers, order, sum),
+                Strategy::TwoPointer => two_pointer_pairs(numbers, order, sum),
+            };
+            found.extend(
+                pairs
+                    .into_iter()
+                    .map(|pair| [chosen.as_slice(), &pair].concat()),
+            );
+        }
+        _ => {
+            for (position, &i) in order.iter().enumerate() {
+                chosen.push(i);
+                let rest = &order[position + 1..];
+                k_sum_from(
+                    numbers,
+                    rest,
+                    k - 1,
+                    sum - numbers[i],
+                    strategy,
+                    chosen,
+                    found,
+                );
+                chosen.pop();
+            }
+        }
+    }
+}
+
+fn hash_pairs<T>(numbers: &[T], order: &[usize], sum: T) -> Vec<[usize; 2]>
+where
+    T: Integer + Hash + Copy,
+{
+    let mut seen: HashMap<T, Vec<usize>> = HashMap::new();
+    let mut pairs = vec![];
+    for &j in order {
+        if let Some(earlier) = seen.get(&(sum - numbers[j])) {
+            pairs.extend(earlier.iter().map(|&i| [i, j]));
         }
+        seen.entry(numbers[j]).or_default().push(j);
     }
-    valid_numbers.into_iter().collect()
+    pairs
+}
+
+// `order` has to be sorted by value
+fn two_pointer_pairs<T>(numbers: &[T], order: &[usize], sum: T) -> Vec<[usize; 2]>
+where
+    T: Integer + Copy,
+{
+    let mut pairs = vec![];
+    if order.len() < 2 {
+        return pairs;
+    }
+    let value = |position: usize| numbers[order[position]];
+    let (mut low, mut high) = (0, order.len() - 1);
+    while low < high {
+        match (value(low) + value(high)).cmp(&sum) {
+            Ordering::Less => low += 1,
+            Ordering::Greater => high -= 1,
+            Ordering::Equal if value(low) == value(high) => {
+                // Everything in between has the same value, so any two of them match
+                for i in low..high {
+                    for j in i + 1..=high {
+                        pairs.push([order[i], order[j]]);
+                    }
+                }
+                break;
+            }
+            Ordering::Equal => {
+                let low_end = (low..=high).find(|&i| value(i) != value(low)).unwrap();
+                let high_start = (low..=high)
+                    .rev()
+                    .find(|&j| value(j) != value(high))
+                    .unwrap()
+                    + 1;
+                for i in low..low_end {
+                    for j in high_start..=high {
+                        pairs.push([order[i], order[j]]);
+                    }
+                }
+                low = low_end;
+                high = high_start - 1;
+            }
+        }
+    }
+    pairs
+}
+
+// Number of subsets of the numbers adding up to sum, by subset size: entry n
+// counts the subsets with n elements. Equal numbers at different positions are
+// different elements.
+pub fn subset_sum_counts_by_size(numbers: &[usize], sum: usize) -> Vec<u64> {
+    // ways[s][n]: subsets of the numbers so far with n elements adding up to s
+    let mut ways = vec![vec![0_u64; numbers.len() + 1]; sum + 1];
+    ways[0][0] = 1;
+    for (seen, &number) in numbers.iter().enumerate() {
+        for s in (number..=sum).rev() {
+            for n in (1..=seen + 1).rev() {
+                ways[s][n] += ways[s - number][n - 1];
+            }
+        }
+    }
+    ways.swap_remove(sum)
+}
+
+pub fn count_subset_sums(numbers: &[usize], sum: usize) -> u64 {
+    subset_sum_counts_by_size(numbers, sum).iter().sum()
+}
+
+// First range of at least min_len consecutive numbers adding up to sum, the one
+// ending first and, among those, starting first. Negative numbers are fine,
+// it looks up earlier prefix sums instead of sliding a window.
+pub fn contiguous_sum<T>(numbers: &[T], sum: T, min_len: usize) -> Option<Range<usize>>
+where
+    T: Integer + Hash + Copy,
+{
+    let prefix_sums = numbers
+        .iter()
+        .scan(T::zero(), |total, &number| {
+            *total = *total + number;
+            Some(*total)
+        })
+        .collect_vec();
+    let prefix = |end: usize| {
+        if end == 0 {
+            T::zero()
+        } else {
+            prefix_sums[end - 1]
+        }
+    };
+    // Empty ranges always add up to zero, they are never a match
+    let min_len = min_len.max(1);
+    // Earliest start for every prefix sum far enough behind the current end
+    let mut starts: HashMap<T, usize> = HashMap::new();
+    for end in min_len..=numbers.len() {
+        starts.entry(prefix(end - min_len)).or_insert(end - min_len);
+        if let Some(&start) = starts.get(&(prefix(end) - sum)) {
+            return Some(start..end);
+        }
+    }
+    None
 }
 
 #[cfg(test)]
@@ -49,4 +262,81 @@ mod tests {
         assert!(two_sum(&[], &0).is_empty());
         assert_eq!(two_sum(&[10, 20, 30], &30), vec![[10, 20]]);
     }
+
+    #[test]
+    fn test_three_sum_without_reuse() {
+        // 1010 + 1010 + 0 would need the 1010 twice
+        assert!(three_sum(&[1010, 0, 5], 2020).is_empty());
+        assert_eq!(
+            three_sum(&[1721, 979, 366, 299, 675, 1456], 2020),
+            vec![[366, 675, 979]]
+        );
+    }
+
+    #[test]
+    fn test_k_sum_duplicates() {
+        for strategy in [Strategy::Hash, Strategy::TwoPointer] {
+            assert_eq!(
+                k_sum::<i32, 2>(&[5, 5, 5], 10, strategy),
+                vec![[0, 1], [0, 2], [1, 2]]
+            );
+            assert_eq!(
+                k_sum::<i32, 2>(&[4, 6, 4, 6, 5], 10, strategy),
+                vec![[0, 1], [0, 3], [1, 2], [2, 3]]
+            );
+            assert!(k_sum::<i32, 2>(&[5], 10, strategy).is_empty());
+            assert_eq!(k_sum::<i32, 1>(&[3, 1, 3], 3, strategy), vec![[0], [2]]);
+            assert_eq!(k_sum::<i32, 0>(&[3], 0, strategy), vec![[]]);
+        }
+    }
+
+    #[test]
+    fn test_k_sum_strategies_agree() {
+        let numbers = [3, -1, 4, 1, -5, 9, 2, -6, 5, 3, 5, 0];
+        for sum in -8..12 {
+            assert_eq!(
+                k_sum::<i64, 3>(&numbers, sum, Strategy::Hash),
+                k_sum::<i64, 3>(&numbers, sum, Strategy::TwoPointer)
+            );
+            assert_eq!(
+                k_sum::<i64, 4>(&numbers, sum, Strategy::Hash),
+                k_sum::<i64, 4>(&numbers, sum, Strategy::TwoPointer)
+            );
+        }
+        let brute_force = (0..numbers.len())
+            .combinations(4)
+            .filter(|indices| indices.iter().map(|&i| numbers[i]).sum::<i64>() == 7)
+            .count();
+        assert_eq!(
+            k_sum::<i64, 4>(&numbers, 7, Strategy::Hash).len(),
+            brute_force
+        );
+    }
+
+    #[test]
+    fn test_subset_sums() {
+        // Containers of 20, 15, 10, 5 and 5 liters holding 25 liters
+        let containers = [20, 15, 10, 5, 5];
+        assert_eq!(count_subset_sums(&containers, 25), 4);
+        assert_eq!(
+            subset_sum_counts_by_size(&containers, 25),
+            vec![0, 0, 3, 1, 0, 0]
+        );
+        assert_eq!(count_subset_sums(&[], 0), 1);
+        assert_eq!(count_subset_sums(&[0, 1], 1), 2);
+    }
+
+    #[test]
+    fn test_contiguous_sum() {
+        let numbers = [
+            35, 20, 15, 25, 47, 40, 62, 55, 65, 95, 102, 117, 150, 182, 127,
+        ];
+        assert_eq!(contiguous_sum(&numbers, 127, 2), Some(2..6));
+        assert_eq!(contiguous_sum(&numbers, 127, 1), Some(2..6));
+        assert_eq!(contiguous_sum(&numbers, 1000, 2), None);
+        assert_eq!(contiguous_sum(&[3, -2, 5, -1], 2, 1), Some(1..4));
+        assert_eq!(contiguous_sum(&[1, 2, 3], 3, 2), Some(0..2));
+        assert_eq!(contiguous_sum(&[1, 2, 3], 3, 1), Some(0..2));
+        assert_eq!(contiguous_sum(&[1, 2, 3], 0, 0), None);
+    }
 }
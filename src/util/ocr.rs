@@ -0,0 +1,296 @@
+#![allow(unused)]
+use std::{collections::HashSet, fmt::Display};
+
+use itertools::Itertools;
+
+// Capital letters as drawn by the puzzles, '#' is a lit pixel. Letters are
+// split on empty columns, so only the lit part of each glyph matters.
+const SMALL_HEIGHT: usize = 6;
+const SMALL_FONT: [(char, &str); 16] = [
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+const LARGE_HEIGHT: usize = 10;
+const LARGE_FONT: [(char, &str); 15] = [
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+    (
+        'Z',
+        "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    // Nothing is lit
+    Empty,
+    // Only the 6 and 10 pixel high fonts are known
+    UnsupportedHeight(usize),
+    // Glyph with the given position from the left did not match any letter
+    UnknownGlyph { position: usize, glyph: String },
+}
+
+impl Display for OcrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OcrError::Empty => write!(f, "No lit pixels to read"),
+            OcrError::UnsupportedHeight(height) => write!(
+                f,
+                "Letters are {} pixels high, only {} and {} are supported",
+                height, SMALL_HEIGHT, LARGE_HEIGHT
+            ),
+            OcrError::UnknownGlyph { position, glyph } => {
+                write!(f, "Unknown glyph at position {}:\n{}", position, glyph)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+// Lit pixels as (x, y) with y growing downwards, like the rows of a picture.
+type Pixels = HashSet<(i64, i64)>;
+// Sorted lit pixels of a single letter
+type Glyph = Vec<(i64, i64)>;
+
+fn pixels_from_picture(picture: &str) -> Pixels {
+    picture
+        .lines()
+        .enumerate()
+        .flat_map(|(y, line)| {
+            line.chars()
+                .enumerate()
+                .filter(|(_, c)| *c == '#')
+                .map(move |(x, _)| (x as i64, y as i64))
+        })
+        .collect()
+}
+
+// Canonical form of a glyph: its lit pixels shifted so that the leftmost lit
+// column is x = 0 and the top row of the text is y = 0.
+fn normalize_glyph(pixels: &[(i64, i64)], top: i64) -> Glyph {
+    let left = pixels.iter().map(|(x, _)| *x).min().unwrap_or(0);
+    pixels
+        .iter()
+        .map(|(x, y)| (x - left, y - top))
+        .sorted()
+        .collect()
+}
+
+fn glyph_to_string(glyph: &[(i64, i64)], height: usize) -> String {
+    let width = glyph.iter().map(|(x, _)| x + 1).max().unwrap_or(0);
+    (0..height as i64)
+        .map(|y| {
+            (0..width)
+                .map(|x| if glyph.contains(&(x, y)) { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+fn font(height: usize) -> Result<Vec<(char, Glyph)>, OcrError> {
+    let letters: &[(char, &str)] = match height {
+        SMALL_HEIGHT => &SMALL_FONT,
+        LARGE_HEIGHT => &LARGE_FONT,
+        _ => return Err(OcrError::UnsupportedHeight(height)),
+    };
+    Ok(letters
+        .iter()
+        .map(|(letter, picture)| {
+            let pixels = pixels_from_picture(picture).into_iter().collect_vec();
+            (*letter, normalize_glyph(&pixels, 0))
+        })
+        .collect())
+}
+
+// Reads the letters drawn by the lit pixels, (x, y) with y growing downwards.
+pub fn recognize_points(points: impl IntoIterator<Item = (i64, i64)>) -> Result<String, OcrError> {
+    let pixels: Pixels = points.into_iter().collect();
+    if pixels.is_empty() {
+        return Err(OcrError::Empty);
+    }
+    let (top, bottom) = pixels
+        .iter()
+        .map(|(_, y)| *y)
+        .minmax()
+        .into_option()
+        .unwrap();
+    let height = (bottom - top + 1) as usize;
+    let font = font(height)?;
+
+    // Columns without lit pixels separate the letters
+    let columns = pixels
+        .iter()
+        .map(|(x, _)| *x)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .sorted()
+        .collect_vec();
+    let groups = columns
+        .into_iter()
+        .enumerate()
+        .chunk_by(|(i, x)| x - *i as i64)
+        .into_iter()
+        .map(|(_, group)| group.map(|(_, x)| x).collect::<HashSet<_>>())
+        .collect_vec();
+
+    groups
+        .iter()
+        .enumerate()
+        .map(|(position, columns)| {
+            let glyph = normalize_glyph(
+                &pixels
+                    .iter()
+                    .filter(|(x, _)| columns.contains(x))
+                    .copied()
+                    .collect_vec(),
+                top,
+            );
+            font.iter()
+                .find(|(_, letter)| *letter == glyph)
+                .map(|(letter, _)| *letter)
+                .ok_or_else(|| OcrError::UnknownGlyph {
+                    position,
+                    glyph: glyph_to_string(&glyph, height),
+                })
+        })
+        .collect()
+}
+
+// Reads the letters of a picture where '#' is lit and any other character is dark.
+pub fn recognize(picture: &str) -> Result<String, OcrError> {
+    recognize_points(pixels_from_picture(picture))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn small_font() {
+        let picture = "\
+####..##..#.....##..#..#.#....###...##..
+#....#..#.#....#..#.#..#.#....#..#.#..#.
+###..#..#.#....#....#..#.#....#..#.#....
+#....####.#....#.##.#..#.#....###..#.##.
+#....#..#.#....#..#.#..#.#....#....#..#.
+####.#..#.####..###..##..####.#.....###.";
+        assert_eq!(recognize(picture), Ok("EALGULPG".to_string()));
+        // Trailing empty lines are not part of the picture
+        let letter = recognize("#..#\n#..#\n####\n#..#\n#..#\n#..#\n\n\n\n\n");
+        assert_eq!(letter, Ok("H".to_string()));
+    }
+
+    #[test]
+    fn every_letter() {
+        for (font, height) in [
+            (&SMALL_FONT[..], SMALL_HEIGHT),
+            (&LARGE_FONT[..], LARGE_HEIGHT),
+        ] {
+            let rows = (0..height)
+                .map(|row| {
+                    font.iter()
+                        .map(|(_, picture)| picture.lines().nth(row).unwrap())
+                        .join("..")
+                })
+                .join("\n");
+            let expected = font.iter().map(|(letter, _)| letter).collect::<String>();
+            assert_eq!(recognize(&rows), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn points_with_offset() {
+        let points = pixels_from_picture(SMALL_FONT[0].1)
+            .into_iter()
+            .map(|(x, y)| (x + 100, y - 20));
+        assert_eq!(recognize_points(points), Ok("A".to_string()));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(recognize("...\n..."), Err(OcrError::Empty));
+        assert_eq!(recognize("#\n#\n#"), Err(OcrError::UnsupportedHeight(3)));
+        let unknown = recognize("####.#\n#....#\n#....#\n#....#\n#....#\n####.#").unwrap_err();
+        assert_eq!(
+            unknown,
+            OcrError::UnknownGlyph {
+                position: 0,
+                glyph: "####\n#...\n#...\n#...\n#...\n####".to_string()
+            }
+        );
+        assert!(unknown
+            .to_string()
+            .starts_with("Unknown glyph at position 0"));
+    }
+}
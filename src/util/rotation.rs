@@ -0,0 +1,318 @@
+#![allow(unused)]
+use std::{collections::HashSet, ops::Mul};
+
+use itertools::Itertools;
+
+pub type Vec3 = [i64; 3];
+
+pub fn add(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn manhattan(a: Vec3, b: Vec3) -> i64 {
+    (0..3).map(|axis| (a[axis] - b[axis]).abs()).sum()
+}
+
+// Squared euclidean distance, unchanged by any rotation or translation.
+fn distance_squared(a: Vec3, b: Vec3) -> i64 {
+    (0..3).map(|axis| (a[axis] - b[axis]).pow(2)).sum()
+}
+
+// Rotation of the integer lattice, stored as a 3x3 matrix acting on column vectors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rotation {
+    matrix: [[i64; 3]; 3],
+}
+
+impl Rotation {
+    pub fn identity() -> Rotation {
+        Rotation {
+            matrix: [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
+        }
+    }
+
+    // The 24 orientations of a cube: every signed permutation matrix with
+    // determinant 1. Mirror images (determinant -1) are left out.
+    pub fn all() -> Vec<Rotation> {
+        (0..3)
+            .permutations(3)
+            .cartesian_product(0..8)
+            .map(|(axes, signs)| {
+                let mut matrix = [[0; 3]; 3];
+                for (row, &axis) in axes.iter().enumerate() {
+                    matrix[row][axis] = if signs & (1 << row) == 0 { 1 } else { -1 };
+                }
+                Rotation { matrix }
+            })
+            .filter(|rotation| rotation.determinant() == 1)
+            .collect()
+    }
+
+    pub fn determinant(&self) -> i64 {
+        let m = &self.matrix;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn apply(&self, point: Vec3) -> Vec3 {
+        let mut rotated = [0; 3];
+        for (row, value) in rotated.iter_mut().enumerate() {
+            *value = (0..3).map(|col| self.matrix[row][col] * point[col]).sum();
+        }
+        rotated
+    }
+
+    // Rotation doing `other` first and `self` second.
+    pub fn compose(&self, other: &Rotation) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (row, values) in matrix.iter_mut().enumerate() {
+            for (col, value) in values.iter_mut().enumerate() {
+                *value = (0..3)
+                    .map(|k| self.matrix[row][k] * other.matrix[k][col])
+                    .sum();
+            }
+        }
+        Rotation { matrix }
+    }
+
+    // Rotation matrices are orthogonal, so the transpose undoes them.
+    pub fn inverse(&self) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (row, values) in matrix.iter_mut().enumerate() {
+            for (col, value) in values.iter_mut().enumerate() {
+                *value = self.matrix[col][row];
+            }
+        }
+        Rotation { matrix }
+    }
+}
+
+impl Mul for Rotation {
+    type Output = Rotation;
+
+    fn mul(self, other: Rotation) -> Rotation {
+        self.compose(&other)
+    }
+}
+
+// Rotation followed by a translation, maps p to rotation * p + translation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Transform {
+    pub rotation: Rotation,
+    pub translation: Vec3,
+}
+
+impl Transform {
+    pub fn identity() -> Transform {
+        Transform {
+            rotation: Rotation::identity(),
+            translation: [0; 3],
+        }
+    }
+
+    pub fn apply(&self, point: Vec3) -> Vec3 {
+        add(self.rotation.apply(point), self.translation)
+    }
+
+    // Transform doing `other` first and `self` second.
+    pub fn compose(&self, other: &Transform) -> Transform {
+        Transform {
+            rotation: self.rotation.compose(&other.rotation),
+            translation: self.apply(other.translation),
+        }
+    }
+
+    pub fn inverse(&self) -> Transform {
+        let rotation = self.rotation.inverse();
+        let translation = rotation.apply(self.translation);
+        Transform {
+            rotation,
+            translation: [-translation[0], -translation[1], -translation[2]],
+        }
+    }
+}
+
+// Points together with, for every point, the sorted distances to all other
+// points. Two points can only be the same after alignment if they share
+// enough of those distances, which rules out most candidate pairs cheaply.
+#[derive(Debug, Clone)]
+pub struct PointCloud {
+    points: Vec<Vec3>,
+    lookup: HashSet<Vec3>,
+    fingerprints: Vec<Vec<i64>>,
+}
+
+impl PointCloud {
+    pub fn new(points: Vec<Vec3>) -> PointCloud {
+        let fingerprints = points
+            .iter()
+            .map(|&a| {
+                points
+                    .iter()
+                    .filter(|&&b| b != a)
+                    .map(|&b| distance_squared(a, b))
+                    .sorted()
+                    .collect_vec()
+            })
+            .collect_vec();
+        PointCloud {
+            lookup: points.iter().copied().collect(),
+            points,
+            fingerprints,
+        }
+    }
+
+    pub fn points(&self) -> &[Vec3] {
+        &self.points
+    }
+
+    // Transform that maps at least `min_matches` points of `other` onto points
+    // of `self`, if one exists.
+    pub fn align(&self, other: &PointCloud, min_matches: usize) -> Option<Transform> {
+        let rotations = Rotation::all();
+        for (i, &anchor) in self.points.iter().enumerate() {
+            for (j, &other_anchor) in other.points.iter().enumerate() {
+                let shared = shared_count(&self.fingerprints[i], &other.fingerprints[j]);
+                if shared + 1 < min_matches {
+                    continue;
+                }
+                for rotation in &rotations {
+                    let transform = Transform {
+                        rotation: *rotation,
+                        translation: sub(anchor, rotation.apply(other_anchor)),
+                    };
+                    let matches = other
+                        .points
+                        .iter()
+                        .filter(|&&point| self.lookup.contains(&transform.apply(point)))
+                        .count();
+                    if matches >= min_matches {
+                        return Some(transform);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+// Size of the multiset intersection of two sorted lists.
+fn shared_count(a: &[i64], b: &[i64]) -> usize {
+    let (mut i, mut j, mut count) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                count += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn twenty_four_distinct_rotations() {
+        let rotations = Rotation::all();
+        assert_eq!(rotations.len(), 24);
+        assert_eq!(rotations.iter().collect::<HashSet<_>>().len(), 24);
+        let point = [1, 2, 3];
+        assert_eq!(
+            rotations
+                .iter()
+                .map(|rotation| rotation.apply(point))
+                .collect::<HashSet<_>>()
+                .len(),
+            24
+        );
+    }
+
+    #[test]
+    fn closed_under_composition() {
+        let rotations = Rotation::all().into_iter().collect::<HashSet<_>>();
+        for a in &rotations {
+            for b in &rotations {
+                assert!(rotations.contains(&(*a * *b)));
+            }
+        }
+    }
+
+    #[test]
+    fn inverse() {
+        for rotation in Rotation::all() {
+            assert_eq!(rotation.compose(&rotation.inverse()), Rotation::identity());
+            assert_eq!(
+                rotation.inverse().apply(rotation.apply([4, -5, 6])),
+                [4, -5, 6]
+            );
+        }
+        let transform = Transform {
+            rotation: Rotation::all()[7],
+            translation: [10, -3, 2],
+        };
+        let point = [1, 7, -9];
+        assert_eq!(transform.inverse().apply(transform.apply(point)), point);
+        assert_eq!(
+            transform.compose(&transform.inverse()),
+            Transform::identity()
+        );
+    }
+
+    #[test]
+    fn compose_applies_right_first() {
+        let rotations = Rotation::all();
+        let a = Transform {
+            rotation: rotations[3],
+            translation: [1, 2, 3],
+        };
+        let b = Transform {
+            rotation: rotations[17],
+            translation: [-4, 0, 8],
+        };
+        let point = [5, -6, 7];
+        assert_eq!(a.compose(&b).apply(point), a.apply(b.apply(point)));
+    }
+
+    #[test]
+    fn align_rotated_cloud() {
+        let points = vec![
+            [0, 0, 0],
+            [3, 1, -2],
+            [-5, 4, 7],
+            [8, -3, 1],
+            [2, 9, -6],
+            [-7, -7, 3],
+        ];
+        let hidden = Transform {
+            rotation: Rotation::all()[13],
+            translation: [100, -20, 35],
+        };
+        let reference = PointCloud::new(points.clone());
+        let moved = PointCloud::new(points.iter().map(|&p| hidden.apply(p)).collect());
+        let found = reference.align(&moved, 6).unwrap();
+        for &point in &points {
+            assert_eq!(found.apply(hidden.apply(point)), point);
+        }
+        assert_eq!(found, hidden.inverse());
+    }
+
+    #[test]
+    fn align_needs_enough_matches() {
+        let reference = PointCloud::new(vec![[0, 0, 0], [1, 2, 3], [7, 7, 7]]);
+        let other = PointCloud::new(vec![[0, 0, 0], [1, 2, 3], [50, 0, 0]]);
+        assert!(reference.align(&other, 2).is_some());
+        assert!(reference.align(&other, 3).is_none());
+    }
+}
@@ -0,0 +1,389 @@
+#![allow(unused)]
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+use std::ops::Sub;
+
+use itertools::Itertools;
+
+// Counters to tune a search, log them once the search is done.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    // States whose successors were computed
+    pub expanded: usize,
+    // States answered from the cache instead of being expanded again
+    pub cache_hits: usize,
+    // States discarded because their bound could not beat the best solution
+    pub pruned: usize,
+    // Largest number of states waiting to be expanded at once
+    pub max_frontier: usize,
+}
+
+// Cache for recursive searches keyed by the search state.
+// The recursion goes through `get_or_compute`, which hands the memo back to the closure:
+//
+// fn count(memo: &mut Memo<State, u64>, state: State) -> u64 {
+//     memo.get_or_compute(state.clone(), |memo| next(&state).map(|s| count(memo, s)).sum())
+// }
+#[derive(Debug, Clone)]
+pub struct Memo<State, Value> {
+    cache: HashMap<State, Value>,
+    pub stats: Stats,
+}
+
+impl<State, Value> Memo<State, Value>
+where
+    State: Eq + Hash,
+    Value: Clone,
+{
+    pub fn new() -> Memo<State, Value> {
+        Memo {
+            cache: HashMap::new(),
+            stats: Stats::default(),
+        }
+    }
+
+    pub fn get_or_compute(
+        &mut self,
+        state: State,
+        compute: impl FnOnce(&mut Self) -> Value,
+    ) -> Value {
+        if let Some(value) = self.cache.get(&state) {
+            self.stats.cache_hits += 1;
+            return value.clone();
+        }
+        self.stats.expanded += 1;
+        let value = compute(self);
+        self.cache.insert(state, value.clone());
+        value
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+}
+
+impl<State, Value> Default for Memo<State, Value>
+where
+    State: Eq + Hash,
+    Value: Clone,
+{
+    fn default() -> Self {
+        Memo::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution<State, V> {
+    pub value: V,
+    pub state: State,
+    pub stats: Stats,
+}
+
+struct Candidate<State, V> {
+    bound: V,
+    state: State,
+}
+
+// Only the bound matters for the order in the queue.
+impl<State, V: Ord> Ord for Candidate<State, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.bound.cmp(&other.bound)
+    }
+}
+
+impl<State, V: Ord> PartialOrd for Candidate<State, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<State, V: Ord> PartialEq for Candidate<State, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bound == other.bound
+    }
+}
+
+impl<State, V: Ord> Eq for Candidate<State, V> {}
+
+// Best-first branch and bound maximizing `value` over all states reachable from `start`.
+// `upper_bound` must never be lower than the value of any state reachable from the given one.
+// States are expanded in order of their bound, so the search stops as soon as the most
+// promising state left can not beat the best value found.
+pub fn branch_and_bound<State, V, I>(
+    start: State,
+    successors: impl Fn(&State) -> I,
+    value: impl Fn(&State) -> V,
+    upper_bound: impl Fn(&State) -> V,
+) -> Solution<State, V>
+where
+    State: Clone,
+    V: Ord + Copy,
+    I: IntoIterator<Item = State>,
+{
+    let mut stats = Stats::default();
+    let mut best = (value(&start), start.clone());
+    let mut heap = BinaryHeap::from([Candidate {
+        bound: upper_bound(&start),
+        state: start,
+    }]);
+
+    while let Some(Candidate { bound, state }) = heap.pop() {
+        if bound <= best.0 {
+            stats.pruned += heap.len() + 1;
+            break;
+        }
+        stats.expanded += 1;
+        for next in successors(&state) {
+            let next_value = value(&next);
+            if next_value > best.0 {
+                best = (next_value, next.clone());
+            }
+            let next_bound = upper_bound(&next);
+            if next_bound > best.0 {
+                heap.push(Candidate {
+                    bound: next_bound,
+                    state: next,
+                });
+            } else {
+                stats.pruned += 1;
+            }
+        }
+        stats.max_frontier = stats.max_frontier.max(heap.len());
+    }
+
+    Solution {
+        value: best.0,
+        state: best.1,
+        stats,
+    }
+}
+
+// Beam search maximizing `score`: every layer only keeps the `width` best scoring successors.
+// Fast but inexact, the best solution may be cut off early. Stops after `max_depth` layers
+// or when no successors are left.
+pub fn beam_search<State, V, I>(
+    start: State,
+    successors: impl Fn(&State) -> I,
+    score: impl Fn(&State) -> V,
+    width: usize,
+    max_depth: usize,
+) -> Solution<State, V>
+where
+    State: Clone,
+    V: Ord + Copy,
+    I: IntoIterator<Item = State>,
+{
+    let mut stats = Stats::default();
+    let mut best = (score(&start), start.clone());
+    let mut beam = vec![start];
+
+    for _ in 0..max_depth {
+        let mut layer = beam
+            .iter()
+            .flat_map(|state| {
+                stats.expanded += 1;
+                successors(state)
+            })
+            .map(|state| (score(&state), state))
+            .collect_vec();
+        if layer.is_empty() {
+            break;
+        }
+        layer.sort_by_key(|(score, _)| Reverse(*score));
+        stats.pruned += layer.len().saturating_sub(width);
+        layer.truncate(width);
+        stats.max_frontier = stats.max_frontier.max(layer.len());
+
+        if layer[0].0 > best.0 {
+            best = layer[0].clone();
+        }
+        beam = layer.into_iter().map(|(_, state)| state).collect();
+    }
+
+    Solution {
+        value: best.0,
+        state: best.1,
+        stats,
+    }
+}
+
+fn depth_limited<State, C, I>(
+    path: &mut Vec<State>,
+    successors: &impl Fn(&State) -> I,
+    is_goal: &impl Fn(&State) -> bool,
+    cost: &impl Fn(&State) -> C,
+    depth: usize,
+    budget: Option<C>,
+    stats: &mut Stats,
+) -> bool
+where
+    State: PartialEq,
+    C: Ord + Copy + Sub<Output = C>,
+    I: IntoIterator<Item = State>,
+{
+    let current = path.last().expect("Path starts with `start`");
+    if is_goal(current) {
+        return true;
+    }
+    if depth == 0 {
+        return false;
+    }
+    stats.expanded += 1;
+    for next in successors(current) {
+        let step = cost(&next);
+        // Never walk in circles on the current path
+        if path.contains(&next) || budget.is_some_and(|left| step > left) {
+            stats.pruned += 1;
+            continue;
+        }
+        path.push(next);
+        stats.max_frontier = stats.max_frontier.max(path.len());
+        if depth_limited(
+            path,
+            successors,
+            is_goal,
+            cost,
+            depth - 1,
+            budget.map(|left| left - step),
+            stats,
+        ) {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+// Iterative deepening DFS: depth first searches with a growing depth limit up to `max_depth`,
+// so the path found has the fewest steps while memory stays linear in the depth.
+// Entering a state costs `cost(state)`, the start is free. States that would push the
+// cost accumulated along the path above `max_cost` are not entered.
+pub fn iterative_deepening<State, C, I>(
+    start: State,
+    successors: impl Fn(&State) -> I,
+    is_goal: impl Fn(&State) -> bool,
+    cost: impl Fn(&State) -> C,
+    max_depth: usize,
+    max_cost: Option<C>,
+) -> (Option<Vec<State>>, Stats)
+where
+    State: PartialEq,
+    C: Ord + Copy + Sub<Output = C>,
+    I: IntoIterator<Item = State>,
+{
+    let mut stats = Stats::default();
+    let mut path = vec![start];
+    for depth in 0..=max_depth {
+        if depth_limited(
+            &mut path,
+            &successors,
+            &is_goal,
+            &cost,
+            depth,
+            max_cost,
+            &mut stats,
+        ) {
+            return (Some(path), stats);
+        }
+    }
+    (None, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    fn fibonacci(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+        memo.get_or_compute(n, |memo| {
+            if n < 2 {
+                n
+            } else {
+                fibonacci(memo, n - 1) + fibonacci(memo, n - 2)
+            }
+        })
+    }
+
+    #[test]
+    fn memoized_recursion() {
+        let mut memo = Memo::new();
+        assert_eq!(fibonacci(&mut memo, 90), 2880067194370816120);
+        assert_eq!(memo.len(), 91);
+        assert_eq!(memo.stats.expanded, 91);
+        assert_eq!(memo.stats.cache_hits, 88);
+    }
+
+    // 0/1 knapsack, the state is (next item, weight, value)
+    const ITEMS: [(i32, i32); 4] = [(5, 10), (4, 40), (6, 30), (3, 50)];
+    const CAPACITY: i32 = 10;
+
+    fn pack(&(item, weight, value): &(usize, i32, i32)) -> Vec<(usize, i32, i32)> {
+        let skip = (item < ITEMS.len()).then_some((item + 1, weight, value));
+        let take = ITEMS.get(item).and_then(|(item_weight, item_value)| {
+            (weight + item_weight <= CAPACITY).then_some((
+                item + 1,
+                weight + item_weight,
+                value + item_value,
+            ))
+        });
+        skip.into_iter().chain(take).collect()
+    }
+
+    #[test]
+    fn knapsack() {
+        let solution = branch_and_bound(
+            (0, 0, 0),
+            pack,
+            |state| state.2,
+            |&(item, _, value)| value + ITEMS[item..].iter().map(|(_, value)| value).sum::<i32>(),
+        );
+        assert_eq!(solution.value, 90);
+        assert_eq!(solution.state.1, 7);
+        assert!(solution.stats.pruned > 0);
+        assert!(solution.stats.expanded < 2_usize.pow(ITEMS.len() as u32 + 1));
+    }
+
+    #[test]
+    fn beam() {
+        let solution = beam_search((0, 0, 0), pack, |state| state.2, 16, ITEMS.len());
+        assert_eq!(solution.value, 90);
+
+        // Greedy: taking the 10 first blocks the best combination
+        let greedy = beam_search((0, 0, 0), pack, |state| state.2, 1, ITEMS.len());
+        assert_eq!(greedy.value, 50);
+        assert_eq!(greedy.stats.max_frontier, 1);
+    }
+
+    // Walk on the number line, every step costs the number stepped on
+    fn steps(n: &i32) -> Vec<i32> {
+        vec![n + 1, n + 3, n - 1]
+    }
+
+    #[test]
+    fn iterative_deepening_finds_fewest_steps() {
+        let (path, _) = iterative_deepening(0, steps, |n| *n == 7, |n| *n, 10, None);
+        assert_eq!(path, Some(vec![0, 1, 4, 7]));
+
+        let (path, _) = iterative_deepening(0, steps, |n| *n == 7, |n| *n, 2, None);
+        assert_eq!(path, None);
+    }
+
+    #[test]
+    fn iterative_deepening_respects_cost_limit() {
+        // Stepping on 7 alone already costs more than 5
+        let (path, stats) = iterative_deepening(0, steps, |n| *n == 7, |n| *n, 10, Some(5));
+        assert_eq!(path, None);
+        assert!(stats.pruned > 0);
+
+        // 0, -1, -2 costs 1 + 2, no single state costs more than 2 but the path does
+        let (path, _) = iterative_deepening(0, steps, |n| *n == -2, |n| n.abs(), 5, Some(3));
+        assert_eq!(path, Some(vec![0, -1, -2]));
+        let (path, _) = iterative_deepening(0, steps, |n| *n == -2, |n| n.abs(), 5, Some(2));
+        assert_eq!(path, None);
+
+        // 0, 1, 4, 7 costs 12, 0, 3, 6, 7 costs 16
+        let (path, _) = iterative_deepening(0, steps, |n| *n == 7, |n| *n, 10, Some(12));
+        assert_eq!(path, Some(vec![0, 1, 4, 7]));
+    }
+}
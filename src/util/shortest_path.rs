@@ -1,21 +1,68 @@
 #![allow(unused)]
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::hash::Hash;
 
+use itertools::Itertools;
+
+// Costs are anything ordered that can be summed up along a path, starting from `zero`.
+// Tuples compare lexicographically, so `(steps, risk)` prefers fewer steps
+// and only then lower risk.
+pub trait PathCost: Ord + Copy + Debug {
+    fn zero() -> Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_path_cost {
+    ($($t:ty),*) => {
+        $(impl PathCost for $t {
+            fn zero() -> Self {
+                0
+            }
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+        })*
+    };
+}
+impl_path_cost!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl<A: PathCost, B: PathCost> PathCost for (A, B) {
+    fn zero() -> Self {
+        (A::zero(), B::zero())
+    }
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Some((self.0.checked_add(other.0)?, self.1.checked_add(other.1)?))
+    }
+}
+
+impl<A: PathCost, B: PathCost, C: PathCost> PathCost for (A, B, C) {
+    fn zero() -> Self {
+        (A::zero(), B::zero(), C::zero())
+    }
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Some((
+            self.0.checked_add(other.0)?,
+            self.1.checked_add(other.1)?,
+            self.2.checked_add(other.2)?,
+        ))
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq)]
-struct State<Node> {
-    cost: i64,
+struct State<Node, C = i64> {
+    cost: C,
     position: Node,
 }
 
 // The priority queue depends on `Ord`.
 // Explicitly implement the trait so the queue becomes a min-heap
 // instead of a max-heap.
-impl<Node> Ord for State<Node>
+impl<Node, C> Ord for State<Node, C>
 where
     Node: PartialEq + Eq + Ord,
+    C: Ord,
 {
     fn cmp(&self, other: &Self) -> Ordering {
         // Notice that the we flip the ordering on costs.
@@ -29,9 +76,10 @@ where
 }
 
 // `PartialOrd` needs to be implemented as well.
-impl<Node> PartialOrd for State<Node>
+impl<Node, C> PartialOrd for State<Node, C>
 where
     Node: PartialEq + Eq + Ord,
+    C: Ord,
 {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -39,34 +87,35 @@ where
 }
 
 #[derive(Debug)]
-pub struct Edge<Node> {
+pub struct Edge<Node, C = i64> {
     pub node: Node,
-    pub cost: i64,
+    pub cost: C,
 }
 
 // Dijkstra's shortest path algorithm.
 
 // Start at `start` and use `dist` to track the current shortest distance
 // to each node. This implementation isn't memory-efficient as it may leave duplicate
-// nodes in the queue. It also uses `usize::MAX` as a sentinel value,
-// for a simpler implementation.
-pub fn shortest_path<Node>(
-    adj_list: &HashMap<Node, Vec<Edge<Node>>>,
+// nodes in the queue. Nodes not reached yet are missing from `dist`, and edges whose
+// cost would overflow are skipped since no cheapest path can go through them.
+pub fn shortest_path<Node, C>(
+    adj_list: &HashMap<Node, Vec<Edge<Node, C>>>,
     start: &Node,
     goal: &Node,
-) -> Option<i64>
+) -> Option<C>
 where
     Node: Ord + Hash + Clone + Debug,
+    C: PathCost,
 {
     // dist[node] = current shortest distance from `start` to `node`
-    let mut dist = HashMap::<Node, i64>::new();
+    let mut dist = HashMap::<Node, C>::new();
 
     let mut heap = BinaryHeap::new();
 
     // We're at `start`, with a zero cost
-    dist.insert(start.clone(), 0);
+    dist.insert(start.clone(), C::zero());
     heap.push(State {
-        cost: 0,
+        cost: C::zero(),
         position: start.clone(),
     });
 
@@ -88,8 +137,11 @@ where
         // a lower cost going through this node
         if let Some(neighbors) = adj_list.get(&position) {
             for edge in neighbors {
+                let Some(next_cost) = cost.checked_add(edge.cost) else {
+                    continue;
+                };
                 let next = State {
-                    cost: cost + edge.cost,
+                    cost: next_cost,
                     position: edge.node.clone(),
                 };
 
@@ -112,6 +164,279 @@ where
     None
 }
 
+// Dijkstra without a goal: the shortest distance from `start` to every reachable node.
+pub fn shortest_paths<Node, C>(
+    adj_list: &HashMap<Node, Vec<Edge<Node, C>>>,
+    start: &Node,
+) -> HashMap<Node, C>
+where
+    Node: Ord + Hash + Clone + Debug,
+    C: PathCost,
+{
+    let mut dist = HashMap::<Node, C>::new();
+    let mut heap = BinaryHeap::new();
+
+    dist.insert(start.clone(), C::zero());
+    heap.push(State {
+        cost: C::zero(),
+        position: start.clone(),
+    });
+
+    while let Some(State { cost, position }) = heap.pop() {
+        if cost > dist[&position] {
+            continue;
+        }
+        if let Some(neighbors) = adj_list.get(&position) {
+            for edge in neighbors {
+                let Some(next_cost) = cost.checked_add(edge.cost) else {
+                    continue;
+                };
+                if dist
+                    .get(&edge.node)
+                    .is_none_or(|distance| next_cost < *distance)
+                {
+                    dist.insert(edge.node.clone(), next_cost);
+                    heap.push(State {
+                        cost: next_cost,
+                        position: edge.node.clone(),
+                    });
+                }
+            }
+        }
+    }
+    dist
+}
+
+// Breadth first search from `start`, every edge counts as one step regardless of its cost.
+pub fn bfs_distances<Node, C>(
+    adj_list: &HashMap<Node, Vec<Edge<Node, C>>>,
+    start: &Node,
+) -> HashMap<Node, i64>
+where
+    Node: Hash + Eq + Clone,
+{
+    let mut dist = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([start.clone()]);
+    while let Some(position) = queue.pop_front() {
+        let steps = dist[&position];
+        for edge in adj_list.get(&position).into_iter().flatten() {
+            if !dist.contains_key(&edge.node) {
+                dist.insert(edge.node.clone(), steps + 1);
+                queue.push_back(edge.node.clone());
+            }
+        }
+    }
+    dist
+}
+
+fn all_nodes<Node, C>(adj_list: &HashMap<Node, Vec<Edge<Node, C>>>) -> Vec<Node>
+where
+    Node: Ord + Hash + Clone,
+{
+    adj_list
+        .iter()
+        .flat_map(|(node, edges)| {
+            [node]
+                .into_iter()
+                .chain(edges.iter().map(|edge| &edge.node))
+        })
+        .cloned()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .sorted()
+        .collect()
+}
+
+// All-pairs shortest paths via repeated BFS, only valid for unit edge costs.
+// all_pairs[a][b] = number of steps from `a` to `b`, unreachable pairs are missing.
+pub fn all_pairs_bfs<Node, C>(
+    adj_list: &HashMap<Node, Vec<Edge<Node, C>>>,
+) -> HashMap<Node, HashMap<Node, i64>>
+where
+    Node: Ord + Hash + Clone,
+{
+    all_nodes(adj_list)
+        .into_iter()
+        .map(|node| {
+            let dist = bfs_distances(adj_list, &node);
+            (node, dist)
+        })
+        .collect()
+}
+
+// Floyd–Warshall all-pairs shortest paths for arbitrary (non negative cycle) edge costs.
+// all_pairs[a][b] = cost of the cheapest path from `a` to `b`, unreachable pairs are missing.
+pub fn floyd_warshall<Node, C>(
+    adj_list: &HashMap<Node, Vec<Edge<Node, C>>>,
+) -> HashMap<Node, HashMap<Node, C>>
+where
+    Node: Ord + Hash + Clone,
+    C: PathCost,
+{
+    let nodes = all_nodes(adj_list);
+    let index: HashMap<&Node, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node, i))
+        .collect();
+
+    let mut dist = vec![vec![None::<C>; nodes.len()]; nodes.len()];
+    for (i, row) in dist.iter_mut().enumerate() {
+        row[i] = Some(C::zero());
+    }
+    for (node, edges) in adj_list {
+        for edge in edges {
+            let entry = &mut dist[index[node]][index[&edge.node]];
+            *entry = Some(entry.map_or(edge.cost, |cost| cost.min(edge.cost)));
+        }
+    }
+
+    for k in 0..nodes.len() {
+        let via = dist[k].clone();
+        for row in dist.iter_mut() {
+            let Some(to_k) = row[k] else { continue };
+            for (cost, from_k) in row.iter_mut().zip(&via) {
+                if let Some(via_k) = from_k.and_then(|from_k| to_k.checked_add(from_k)) {
+                    if cost.is_none_or(|cost| via_k < cost) {
+                        *cost = Some(via_k);
+                    }
+                }
+            }
+        }
+    }
+
+    nodes
+        .iter()
+        .zip(dist)
+        .map(|(from, row)| {
+            let reachable = nodes
+                .iter()
+                .zip(row)
+                .filter_map(|(to, cost)| cost.map(|cost| (to.clone(), cost)))
+                .collect();
+            (from.clone(), reachable)
+        })
+        .collect()
+}
+
+// Reduce the graph to the nodes for which `keep` holds.
+// Every kept node gets an edge to every other kept node it can reach,
+// weighted with the exact cost of the shortest path in the original graph.
+pub fn contract<Node, C>(
+    adj_list: &HashMap<Node, Vec<Edge<Node, C>>>,
+    keep: impl Fn(&Node) -> bool,
+) -> HashMap<Node, Vec<Edge<Node, C>>>
+where
+    Node: Ord + Hash + Clone + Debug,
+    C: PathCost,
+{
+    all_nodes(adj_list)
+        .into_iter()
+        .filter(|node| keep(node))
+        .map(|from| {
+            let edges = shortest_paths(adj_list, &from)
+                .into_iter()
+                .filter(|(to, _)| *to != from && keep(to))
+                .sorted()
+                .map(|(node, cost)| Edge { node, cost })
+                .collect();
+            (from, edges)
+        })
+        .collect()
+}
+
+// Dijkstra over dense `usize` node ids, `adj_list[node]` holds the outgoing edges of `node`.
+// Distances live in a `Vec` instead of a `HashMap` and every node is settled exactly once,
+// stale queue entries are skipped when popped.
+pub fn dense_shortest_path<C: PathCost>(
+    adj_list: &[Vec<Edge<usize, C>>],
+    start: usize,
+    goal: usize,
+) -> Option<C> {
+    let mut dist = vec![None::<C>; adj_list.len()];
+    let mut settled = vec![false; adj_list.len()];
+    let mut heap = BinaryHeap::new();
+
+    dist[start] = Some(C::zero());
+    heap.push(State {
+        cost: C::zero(),
+        position: start,
+    });
+
+    while let Some(State { cost, position }) = heap.pop() {
+        if position == goal {
+            return Some(cost);
+        }
+        if settled[position] {
+            continue;
+        }
+        settled[position] = true;
+
+        for edge in &adj_list[position] {
+            let Some(next_cost) = cost.checked_add(edge.cost) else {
+                continue;
+            };
+            if dist[edge.node].is_none_or(|distance| next_cost < distance) {
+                dist[edge.node] = Some(next_cost);
+                heap.push(State {
+                    cost: next_cost,
+                    position: edge.node,
+                });
+            }
+        }
+    }
+    None
+}
+
+// Dial's algorithm: Dijkstra with a bucket queue instead of a heap.
+// Only valid for integer edge costs in `0..=max_cost`, which makes it a good fit for grids
+// where every step costs a single digit. The buckets are reused round robin, so at most
+// `max_cost + 1` of them are alive at any time.
+pub fn bucket_shortest_path(
+    adj_list: &[Vec<Edge<usize>>],
+    start: usize,
+    goal: usize,
+    max_cost: usize,
+) -> Option<i64> {
+    let mut dist = vec![None::<i64>; adj_list.len()];
+    let mut buckets = vec![Vec::<usize>::new(); max_cost + 1];
+    let mut queued = 1;
+
+    dist[start] = Some(0);
+    buckets[0].push(start);
+
+    let mut cost = 0;
+    while queued > 0 {
+        let bucket = cost as usize % (max_cost + 1);
+        while let Some(position) = buckets[bucket].pop() {
+            queued -= 1;
+            // Stale entry, the node has been moved to a cheaper bucket
+            if dist[position] != Some(cost) {
+                continue;
+            }
+            if position == goal {
+                return Some(cost);
+            }
+            for edge in &adj_list[position] {
+                assert!(
+                    (0..=max_cost as i64).contains(&edge.cost),
+                    "Edge cost {} outside of 0..={}",
+                    edge.cost,
+                    max_cost
+                );
+                let next_cost = cost + edge.cost;
+                if dist[edge.node].is_none_or(|distance| next_cost < distance) {
+                    dist[edge.node] = Some(next_cost);
+                    buckets[next_cost as usize % (max_cost + 1)].push(edge.node);
+                    queued += 1;
+                }
+            }
+        }
+        cost += 1;
+    }
+    None
+}
+
 fn main() {
     // This is the directed graph we're going to use.
     // The node numbers correspond to the different states,
@@ -160,3 +485,171 @@ fn main() {
     assert_eq!(shortest_path(&graph, &0, &4), Some(5));
     assert_eq!(shortest_path(&graph, &4, &0), None);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    //          1        2
+    //      0 -----> 1 -----> 3
+    //      |        ^        |
+    //      | 10     | 1      | 2
+    //      v        |        v
+    //      2 -------+        4
+    fn example() -> HashMap<usize, Vec<Edge<usize>>> {
+        HashMap::from([
+            (
+                0,
+                vec![Edge { node: 1, cost: 1 }, Edge { node: 2, cost: 10 }],
+            ),
+            (1, vec![Edge { node: 3, cost: 2 }]),
+            (2, vec![Edge { node: 1, cost: 1 }]),
+            (3, vec![Edge { node: 4, cost: 2 }]),
+        ])
+    }
+
+    #[test]
+    fn floyd_warshall_matches_dijkstra() {
+        let graph = example();
+        let all_pairs = floyd_warshall(&graph);
+        for from in 0..5 {
+            for to in 0..5 {
+                assert_eq!(
+                    all_pairs[&from].get(&to).copied(),
+                    shortest_path(&graph, &from, &to)
+                );
+            }
+        }
+        assert_eq!(all_pairs[&0][&4], 5);
+        assert_eq!(all_pairs[&2][&4], 5);
+        assert!(!all_pairs[&4].contains_key(&0));
+    }
+
+    #[test]
+    fn all_pairs_bfs_counts_steps() {
+        let all_pairs = all_pairs_bfs(&example());
+        assert_eq!(all_pairs[&0][&0], 0);
+        assert_eq!(all_pairs[&0][&3], 2);
+        assert_eq!(all_pairs[&2][&4], 3);
+        assert!(!all_pairs[&3].contains_key(&1));
+    }
+
+    fn dense_example() -> Vec<Vec<Edge<usize>>> {
+        let graph = example();
+        (0..5)
+            .map(|node| {
+                graph
+                    .get(&node)
+                    .into_iter()
+                    .flatten()
+                    .map(|edge| Edge {
+                        node: edge.node,
+                        cost: edge.cost,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dense_matches_hashed() {
+        let graph = example();
+        let dense = dense_example();
+        for from in 0..5 {
+            for to in 0..5 {
+                let expected = shortest_path(&graph, &from, &to);
+                assert_eq!(dense_shortest_path(&dense, from, to), expected);
+                assert_eq!(bucket_shortest_path(&dense, from, to, 10), expected);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Edge cost 10 outside of 0..=5")]
+    fn bucket_rejects_large_costs() {
+        bucket_shortest_path(&dense_example(), 0, 4, 5);
+    }
+
+    #[test]
+    fn other_cost_types() {
+        let graph = example()
+            .into_iter()
+            .map(|(node, edges)| {
+                let edges = edges
+                    .into_iter()
+                    .map(|edge| Edge {
+                        node: edge.node,
+                        cost: edge.cost as u32,
+                    })
+                    .collect_vec();
+                (node, edges)
+            })
+            .collect::<HashMap<_, _>>();
+        assert_eq!(shortest_path(&graph, &0, &4), Some(5_u32));
+        assert_eq!(floyd_warshall(&graph)[&2][&3], 3_u32);
+    }
+
+    #[test]
+    fn lexicographic_costs() {
+        // Cost is (steps, risk): the direct but risky edge wins over the longer safe detour
+        let graph = HashMap::from([
+            (
+                'a',
+                vec![
+                    Edge {
+                        node: 'b',
+                        cost: (1_u32, 9_u64),
+                    },
+                    Edge {
+                        node: 'c',
+                        cost: (1, 1),
+                    },
+                ],
+            ),
+            (
+                'c',
+                vec![Edge {
+                    node: 'b',
+                    cost: (1, 1),
+                }],
+            ),
+        ]);
+        assert_eq!(shortest_path(&graph, &'a', &'b'), Some((1, 9)));
+    }
+
+    #[test]
+    fn overflowing_edges_are_skipped() {
+        // 0 -> 1 -> 2 does not fit into a u8, the direct edge does
+        let graph = vec![
+            vec![
+                Edge {
+                    node: 1,
+                    cost: 200_u8,
+                },
+                Edge { node: 2, cost: 250 },
+            ],
+            vec![Edge { node: 2, cost: 100 }],
+            vec![],
+        ];
+        assert_eq!(dense_shortest_path(&graph, 0, 2), Some(250));
+        let hashed = graph.into_iter().enumerate().collect::<HashMap<_, _>>();
+        assert_eq!(shortest_path(&hashed, &0, &2), Some(250));
+        assert_eq!(floyd_warshall(&hashed)[&0][&2], 250);
+    }
+
+    #[test]
+    fn contract_keeps_exact_costs() {
+        let contracted = contract(&example(), |node| *node != 1 && *node != 3);
+        assert_eq!(contracted.len(), 3);
+        let costs = |node| {
+            contracted[&node]
+                .iter()
+                .map(|edge| (edge.node, edge.cost))
+                .collect_vec()
+        };
+        assert_eq!(costs(0), vec![(2, 10), (4, 5)]);
+        assert_eq!(costs(2), vec![(4, 5)]);
+        assert_eq!(costs(4), vec![]);
+    }
+}
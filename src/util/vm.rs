@@ -0,0 +1,432 @@
+#![allow(unused)]
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+// What the vm does with the program counter after an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Next,
+    // Relative to the instruction that was just executed
+    Jump(i64),
+    Halt,
+}
+
+// Registers and instruction set of a puzzle computer. The program, program
+// counter and cycle count are kept by the Vm running it.
+pub trait Machine {
+    type Instruction: Clone + Debug;
+
+    fn parse_instruction(line: &str) -> Result<Self::Instruction, String>;
+
+    // Cycles the instruction takes, its effect is only visible after the last one
+    fn cycles(instruction: &Self::Instruction) -> usize {
+        1
+    }
+
+    fn execute(&mut self, instruction: &Self::Instruction) -> Result<Flow, String>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    // Starting at 1
+    pub line: usize,
+    pub text: String,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Line {} {:?}: {}", self.line, self.text, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// One instruction per line, empty lines are skipped.
+pub fn parse_program<M: Machine>(source: &str) -> Result<Vec<M::Instruction>, ParseError> {
+    source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            M::parse_instruction(line.trim()).map_err(|message| ParseError {
+                line: index + 1,
+                text: line.to_string(),
+                message,
+            })
+        })
+        .collect()
+}
+
+// Opcode and operands of a line like "addx -3" or "add x 4"
+pub fn split_instruction(line: &str) -> (&str, Vec<&str>) {
+    let mut parts = line.split_whitespace();
+    let opcode = parts.next().unwrap_or("");
+    (opcode, parts.collect())
+}
+
+pub fn operand<T>(operands: &[&str], index: usize) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let text = operands
+        .get(index)
+        .ok_or_else(|| format!("Missing operand {}", index + 1))?;
+    text.parse()
+        .map_err(|error| format!("Invalid operand {:?}: {}", text, error))
+}
+
+// Why Vm::run returned. Breakpoints and loops stop before the instruction at
+// the pc is executed, running again continues from there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stop {
+    // Left the program or executed Flow::Halt
+    Halted,
+    PcBreakpoint(usize),
+    // The next instruction is busy during this cycle, so the machine shows the
+    // state during the cycle. Each cycle breakpoint triggers once.
+    CycleBreakpoint(usize),
+    // The instruction at this pc was executed before
+    Loop(usize),
+    Fault { pc: usize, message: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry<I> {
+    // First cycle of the instruction, starting at 1
+    pub cycle: usize,
+    pub pc: usize,
+    pub instruction: I,
+}
+
+pub struct Vm<'a, M: Machine> {
+    machine: M,
+    program: &'a [M::Instruction],
+    pc: i64,
+    // Cycles completed so far
+    cycle: usize,
+    pc_breakpoints: HashSet<usize>,
+    cycle_breakpoints: BTreeSet<usize>,
+    // The pc breakpoint at the current pc was already reported
+    resume: bool,
+    finished: Option<Stop>,
+    visited: Option<HashSet<usize>>,
+    trace: Option<Vec<TraceEntry<M::Instruction>>>,
+}
+
+impl<'a, M: Machine> Vm<'a, M> {
+    pub fn new(machine: M, program: &'a [M::Instruction]) -> Vm<'a, M> {
+        Vm {
+            machine,
+            program,
+            pc: 0,
+            cycle: 0,
+            pc_breakpoints: HashSet::new(),
+            cycle_breakpoints: BTreeSet::new(),
+            resume: false,
+            finished: None,
+            visited: None,
+            trace: None,
+        }
+    }
+
+    pub fn breakpoint_pc(mut self, pc: usize) -> Vm<'a, M> {
+        self.pc_breakpoints.insert(pc);
+        self
+    }
+
+    pub fn breakpoint_cycles(mut self, cycles: impl IntoIterator<Item = usize>) -> Vm<'a, M> {
+        self.cycle_breakpoints.extend(cycles);
+        self
+    }
+
+    // Stop with Stop::Loop instead of executing an instruction a second time.
+    // Only means an endless loop if the control flow depends on the pc alone.
+    pub fn detect_loops(mut self) -> Vm<'a, M> {
+        self.visited = Some(HashSet::new());
+        self
+    }
+
+    // Record every executed instruction
+    pub fn tracing(mut self) -> Vm<'a, M> {
+        self.trace = Some(vec![]);
+        self
+    }
+
+    pub fn machine(&self) -> &M {
+        &self.machine
+    }
+
+    pub fn machine_mut(&mut self) -> &mut M {
+        &mut self.machine
+    }
+
+    pub fn into_machine(self) -> M {
+        self.machine
+    }
+
+    pub fn pc(&self) -> i64 {
+        self.pc
+    }
+
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    // Empty unless tracing
+    pub fn trace(&self) -> &[TraceEntry<M::Instruction>] {
+        self.trace.as_deref().unwrap_or(&[])
+    }
+
+    pub fn run(&mut self) -> Stop {
+        loop {
+            if let Some(stop) = self.step() {
+                return stop;
+            }
+        }
+    }
+
+    // Executes a single instruction, unless the vm stops first
+    pub fn step(&mut self) -> Option<Stop> {
+        if let Some(stop) = &self.finished {
+            return Some(stop.clone());
+        }
+        if self.pc < 0 || self.pc >= self.program.len() as i64 {
+            return Some(Stop::Halted);
+        }
+        let pc = self.pc as usize;
+        let program = self.program;
+        let instruction = &program[pc];
+        if self
+            .visited
+            .as_ref()
+            .is_some_and(|visited| visited.contains(&pc))
+        {
+            return Some(Stop::Loop(pc));
+        }
+        if !self.resume && self.pc_breakpoints.contains(&pc) {
+            self.resume = true;
+            return Some(Stop::PcBreakpoint(pc));
+        }
+        let cycles = M::cycles(instruction);
+        if let Some(&cycle) = self
+            .cycle_breakpoints
+            .range(self.cycle + 1..=self.cycle + cycles)
+            .next()
+        {
+            self.cycle_breakpoints.remove(&cycle);
+            return Some(Stop::CycleBreakpoint(cycle));
+        }
+
+        self.resume = false;
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEntry {
+                cycle: self.cycle + 1,
+                pc,
+                instruction: instruction.clone(),
+            });
+        }
+        if let Some(visited) = &mut self.visited {
+            visited.insert(pc);
+        }
+        self.cycle += cycles;
+        match self.machine.execute(instruction) {
+            Ok(Flow::Next) => self.pc += 1,
+            Ok(Flow::Jump(offset)) => self.pc += offset,
+            Ok(Flow::Halt) => self.finished = Some(Stop::Halted),
+            Err(message) => self.finished = Some(Stop::Fault { pc, message }),
+        }
+        self.finished.clone()
+    }
+}
+
+// Looks for a single instruction to change so that the program halts instead of
+// looping, trying the instructions in order. `patch` gives the replacement for
+// an instruction, None to leave it alone. Returns the index of the changed
+// instruction and the machine after halting.
+pub fn repair<M: Machine>(
+    program: &[M::Instruction],
+    machine: impl Fn() -> M,
+    patch: impl Fn(&M::Instruction) -> Option<M::Instruction>,
+) -> Option<(usize, M)> {
+    (0..program.len()).find_map(|index| {
+        let mut patched = program.to_vec();
+        patched[index] = patch(&program[index])?;
+        let mut vm = Vm::new(machine(), &patched).detect_loops();
+        match vm.run() {
+            Stop::Halted => Some((index, vm.into_machine())),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Op {
+        Nop(i64),
+        Add(i64),
+        // Like add, but takes three cycles
+        Slow(i64),
+        Div(i64),
+        Jmp(i64),
+        Hlt,
+    }
+
+    #[derive(Debug, Default)]
+    struct Accumulator {
+        value: i64,
+    }
+
+    impl Machine for Accumulator {
+        type Instruction = Op;
+
+        fn parse_instruction(line: &str) -> Result<Op, String> {
+            let (opcode, operands) = split_instruction(line);
+            Ok(match opcode {
+                "nop" => Op::Nop(operand(&operands, 0)?),
+                "add" => Op::Add(operand(&operands, 0)?),
+                "slow" => Op::Slow(operand(&operands, 0)?),
+                "div" => Op::Div(operand(&operands, 0)?),
+                "jmp" => Op::Jmp(operand(&operands, 0)?),
+                "hlt" => Op::Hlt,
+                _ => return Err(format!("Unknown opcode {}", opcode)),
+            })
+        }
+
+        fn cycles(instruction: &Op) -> usize {
+            match instruction {
+                Op::Slow(_) => 3,
+                _ => 1,
+            }
+        }
+
+        fn execute(&mut self, instruction: &Op) -> Result<Flow, String> {
+            match *instruction {
+                Op::Nop(_) => {}
+                Op::Add(value) | Op::Slow(value) => self.value += value,
+                Op::Div(0) => return Err("Division by zero".to_string()),
+                Op::Div(value) => self.value /= value,
+                Op::Jmp(offset) => return Ok(Flow::Jump(offset)),
+                Op::Hlt => return Ok(Flow::Halt),
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    fn program(source: &str) -> Vec<Op> {
+        parse_program::<Accumulator>(source).unwrap()
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            program("add +3\n\njmp -1\nhlt"),
+            vec![Op::Add(3), Op::Jmp(-1), Op::Hlt]
+        );
+        let error = parse_program::<Accumulator>("add 1\nmul 2").unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.message, "Unknown opcode mul");
+        let error = parse_program::<Accumulator>("add x").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Line 1 \"add x\": Invalid operand \"x\": invalid digit found in string"
+        );
+        assert_eq!(
+            Accumulator::parse_instruction("jmp"),
+            Err("Missing operand 1".to_string())
+        );
+    }
+
+    #[test]
+    fn run_to_end() {
+        let program = program("add 5\nslow 3\ndiv 2\nhlt\nadd 100");
+        let mut vm = Vm::new(Accumulator::default(), &program);
+        assert_eq!(vm.run(), Stop::Halted);
+        assert_eq!(vm.machine().value, 4);
+        assert_eq!(vm.cycle(), 6);
+        assert_eq!(vm.pc(), 3);
+        assert_eq!(vm.run(), Stop::Halted);
+    }
+
+    #[test]
+    fn breakpoints() {
+        let program = program("add 1\nslow 10\nadd 100\nadd 1000");
+        let mut vm = Vm::new(Accumulator::default(), &program)
+            .breakpoint_pc(2)
+            .breakpoint_cycles([3, 4, 6]);
+        // Cycles 2 to 4 belong to the slow instruction, its result is not there yet
+        assert_eq!(vm.run(), Stop::CycleBreakpoint(3));
+        assert_eq!(vm.machine().value, 1);
+        assert_eq!(vm.run(), Stop::CycleBreakpoint(4));
+        assert_eq!(vm.machine().value, 1);
+        assert_eq!(vm.run(), Stop::PcBreakpoint(2));
+        assert_eq!(vm.machine().value, 11);
+        assert_eq!(vm.cycle(), 4);
+        assert_eq!(vm.run(), Stop::CycleBreakpoint(6));
+        assert_eq!(vm.machine().value, 111);
+        assert_eq!(vm.run(), Stop::Halted);
+        assert_eq!(vm.machine().value, 1111);
+    }
+
+    #[test]
+    fn loops_and_tracing() {
+        let program = program("nop 0\nadd 1\njmp 4\nadd 3\njmp -3\nadd -99\nadd 1\njmp -4\nadd 6");
+        let mut vm = Vm::new(Accumulator::default(), &program)
+            .detect_loops()
+            .tracing();
+        assert_eq!(vm.run(), Stop::Loop(1));
+        assert_eq!(vm.machine().value, 5);
+        assert_eq!(
+            vm.trace().iter().map(|entry| entry.pc).collect::<Vec<_>>(),
+            vec![0, 1, 2, 6, 7, 3, 4]
+        );
+        assert_eq!(
+            vm.trace()[3],
+            TraceEntry {
+                cycle: 4,
+                pc: 6,
+                instruction: Op::Add(1)
+            }
+        );
+
+        let untraced = Vm::new(Accumulator::default(), &program);
+        assert!(untraced.trace().is_empty());
+    }
+
+    #[test]
+    fn repair_single_instruction() {
+        let program = program("nop 0\nadd 1\njmp 4\nadd 3\njmp -3\nadd -99\nadd 1\njmp -4\nadd 6");
+        let swap = |op: &Op| match *op {
+            Op::Nop(value) => Some(Op::Jmp(value)),
+            Op::Jmp(value) => Some(Op::Nop(value)),
+            _ => None,
+        };
+        let (index, machine) = repair(&program, Accumulator::default, swap).unwrap();
+        assert_eq!(index, 7);
+        assert_eq!(machine.value, 8);
+
+        let hopeless = self::program("jmp 0\nadd 1\njmp -1");
+        assert!(repair(&hopeless, Accumulator::default, |_| None).is_none());
+    }
+
+    #[test]
+    fn faults_are_final() {
+        let program = program("add 3\ndiv 0\nadd 1");
+        let mut vm = Vm::new(Accumulator::default(), &program);
+        let fault = Stop::Fault {
+            pc: 1,
+            message: "Division by zero".to_string(),
+        };
+        assert_eq!(vm.run(), fault);
+        assert_eq!(vm.run(), fault);
+        assert_eq!(vm.machine().value, 3);
+    }
+}
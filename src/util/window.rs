@@ -0,0 +1,332 @@
+#![allow(unused)]
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    ops::Sub,
+};
+
+use num::Zero;
+
+// The last `size` values of a stream. Every window below keeps one of these and
+// updates its own state from the value coming in and the one falling out, so
+// moving forward costs amortized O(1) whatever the window size.
+#[derive(Debug, Clone)]
+struct Ring<T> {
+    values: VecDeque<T>,
+    size: usize,
+}
+
+impl<T> Ring<T> {
+    fn new(size: usize) -> Ring<T> {
+        assert!(size > 0, "Window size has to be positive");
+        Ring {
+            values: VecDeque::with_capacity(size + 1),
+            size,
+        }
+    }
+
+    // Returns the value that fell out of the window, if any
+    fn push(&mut self, value: T) -> Option<T> {
+        self.values.push_back(value);
+        if self.values.len() > self.size {
+            self.values.pop_front()
+        } else {
+            None
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn is_full(&self) -> bool {
+        self.values.len() == self.size
+    }
+}
+
+// Number of different values in the window
+#[derive(Debug, Clone)]
+pub struct DistinctCounter<T> {
+    ring: Ring<T>,
+    counts: HashMap<T, usize>,
+}
+
+impl<T> DistinctCounter<T>
+where
+    T: Hash + Eq + Clone,
+{
+    pub fn new(size: usize) -> DistinctCounter<T> {
+        DistinctCounter {
+            ring: Ring::new(size),
+            counts: HashMap::new(),
+        }
+    }
+
+    pub fn push(&mut self, value: T) -> Option<T> {
+        *self.counts.entry(value.clone()).or_insert(0) += 1;
+        let evicted = self.ring.push(value);
+        if let Some(old) = &evicted {
+            let count = self.counts.get_mut(old).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(old);
+            }
+        }
+        evicted
+    }
+
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.ring.is_full()
+    }
+
+    pub fn distinct(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn all_distinct(&self) -> bool {
+        self.distinct() == self.len()
+    }
+
+    pub fn count(&self, value: &T) -> usize {
+        self.counts.get(value).copied().unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RunningSum<T> {
+    ring: Ring<T>,
+    sum: T,
+}
+
+impl<T> RunningSum<T>
+where
+    T: Zero + Sub<Output = T> + Copy,
+{
+    pub fn new(size: usize) -> RunningSum<T> {
+        RunningSum {
+            ring: Ring::new(size),
+            sum: T::zero(),
+        }
+    }
+
+    pub fn push(&mut self, value: T) -> Option<T> {
+        self.sum = self.sum + value;
+        let evicted = self.ring.push(value);
+        if let Some(old) = evicted {
+            self.sum = self.sum - old;
+        }
+        evicted
+    }
+
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.ring.is_full()
+    }
+
+    pub fn sum(&self) -> T {
+        self.sum
+    }
+}
+
+// Smallest and largest value in the window. Each deque holds the values that
+// can still become the minimum (maximum), together with their position in the
+// stream: a value is dropped as soon as a smaller (larger) one arrives after it.
+#[derive(Debug, Clone)]
+pub struct MinMax<T> {
+    ring: Ring<T>,
+    pushed: usize,
+    mins: VecDeque<(usize, T)>,
+    maxs: VecDeque<(usize, T)>,
+}
+
+impl<T> MinMax<T>
+where
+    T: Ord + Clone,
+{
+    pub fn new(size: usize) -> MinMax<T> {
+        MinMax {
+            ring: Ring::new(size),
+            pushed: 0,
+            mins: VecDeque::new(),
+            maxs: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, value: T) -> Option<T> {
+        let index = self.pushed;
+        self.pushed += 1;
+        while self.mins.back().is_some_and(|(_, min)| *min > value) {
+            self.mins.pop_back();
+        }
+        self.mins.push_back((index, value.clone()));
+        while self.maxs.back().is_some_and(|(_, max)| *max < value) {
+            self.maxs.pop_back();
+        }
+        self.maxs.push_back((index, value.clone()));
+
+        let evicted = self.ring.push(value);
+        if evicted.is_some() {
+            let first = self.pushed - self.ring.size;
+            for deque in [&mut self.mins, &mut self.maxs] {
+                while deque.front().is_some_and(|(i, _)| *i < first) {
+                    deque.pop_front();
+                }
+            }
+        }
+        evicted
+    }
+
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.ring.is_full()
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        self.mins.front().map(|(_, min)| min)
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        self.maxs.front().map(|(_, max)| max)
+    }
+}
+
+// Answers whether two values at different positions in the window add up to a
+// given sum. Moving forward is O(1), a query looks up the partner of every
+// distinct value, O(size).
+#[derive(Debug, Clone)]
+pub struct PairSums<T> {
+    values: DistinctCounter<T>,
+}
+
+impl<T> PairSums<T>
+where
+    T: Hash + Eq + Sub<Output = T> + Copy,
+{
+    pub fn new(size: usize) -> PairSums<T> {
+        PairSums {
+            values: DistinctCounter::new(size),
+        }
+    }
+
+    pub fn push(&mut self, value: T) -> Option<T> {
+        self.values.push(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.values.is_full()
+    }
+
+    pub fn has_pair_sum(&self, sum: T) -> bool {
+        self.values.counts.iter().any(|(&value, &count)| {
+            let partner = sum - value;
+            if partner == value {
+                count >= 2
+            } else {
+                self.values.counts.contains_key(&partner)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn distinct_counter() {
+        let mut window = DistinctCounter::new(4);
+        let found = "mjqjpqmgbljsphdztnvjfqwrcgsmlb".chars().position(|c| {
+            window.push(c);
+            window.is_full() && window.all_distinct()
+        });
+        assert_eq!(found, Some(6));
+        assert_eq!(window.distinct(), 4);
+        assert_eq!(window.count(&'j'), 1);
+        assert_eq!(window.count(&'m'), 1);
+        assert_eq!(window.count(&'b'), 0);
+
+        let mut window = DistinctCounter::new(3);
+        assert_eq!(window.push(1), None);
+        assert_eq!(window.push(1), None);
+        assert_eq!(window.push(2), None);
+        assert_eq!(window.distinct(), 2);
+        assert!(!window.all_distinct());
+        assert_eq!(window.push(3), Some(1));
+        assert!(window.all_distinct());
+    }
+
+    #[test]
+    fn running_sum() {
+        let mut window = RunningSum::new(3);
+        let sums = [199, 200, 208, 210, 200, 207]
+            .into_iter()
+            .filter_map(|depth| {
+                window.push(depth);
+                window.is_full().then(|| window.sum())
+            })
+            .collect::<Vec<i32>>();
+        assert_eq!(sums, vec![607, 618, 618, 617]);
+
+        let mut window = RunningSum::new(2);
+        window.push(0.5);
+        window.push(0.25);
+        window.push(1.0);
+        assert_eq!(window.sum(), 1.25);
+    }
+
+    #[test]
+    fn min_max_matches_brute_force() {
+        let values = [5, 1, 4, 4, 9, 2, 6, 2, 8, 3, 7, 0, 0, 5];
+        for size in 1..=values.len() {
+            let mut window = MinMax::new(size);
+            for (i, &value) in values.iter().enumerate() {
+                window.push(value);
+                let start = (i + 1).saturating_sub(size);
+                let expected = &values[start..=i];
+                assert_eq!(window.min(), expected.iter().min());
+                assert_eq!(window.max(), expected.iter().max());
+                assert_eq!(window.len(), expected.len());
+            }
+        }
+        assert_eq!(MinMax::<u8>::new(3).min(), None);
+    }
+
+    #[test]
+    fn pair_sums() {
+        let mut window = PairSums::new(5);
+        for value in [35, 20, 15, 25, 47] {
+            window.push(value);
+        }
+        assert!(window.has_pair_sum(40));
+        assert!(!window.has_pair_sum(70));
+        assert!(!window.has_pair_sum(94));
+        window.push(47);
+        assert!(window.has_pair_sum(94));
+        window.push(1);
+        window.push(2);
+        window.push(3);
+        window.push(4);
+        assert!(!window.has_pair_sum(94));
+    }
+
+    #[test]
+    #[should_panic(expected = "Window size has to be positive")]
+    fn empty_window() {
+        RunningSum::<i32>::new(0);
+    }
+}